@@ -22,7 +22,7 @@ use runtime_primitives::{
 };
 use client::{
 	block_builder::api::{CheckInherentsResult, InherentData, self as block_builder_api},
-	runtime_api, impl_runtime_apis
+	runtime_api, impl_runtime_apis, decl_runtime_apis
 };
 use version::RuntimeVersion;
 #[cfg(feature = "std")]
@@ -50,8 +50,11 @@ pub type BlockNumber = u64;
 /// Index of an account's extrinsic in the chain.
 pub type Nonce = u64;
 
+/// The type for recording an account's balance.
+pub type Balance = u128;
+
 // Declare the use of substratekitties module
-mod substratekitties;
+pub mod substratekitties;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -157,7 +160,7 @@ impl timestamp::Trait for Runtime {
 
 impl balances::Trait for Runtime {
 	/// The type for recording an account's balance.
-	type Balance = u128;
+	type Balance = Balance;
 	/// What to do if an account's free balance gets zeroed.
 	type OnFreeBalanceZero = ();
 	/// What to do if a new account is created.
@@ -177,6 +180,18 @@ impl sudo::Trait for Runtime {
 // Implementation of substratekitties module
 impl substratekitties::Trait for Runtime {
 	type Event = Event;
+	/// Use the system module's random seed for kitty ids and DNA.
+	#[cfg(not(feature = "test-rng"))]
+	type Randomness = System;
+	/// Reproducible ids and DNA for test scenarios; see the `test-rng` feature in Cargo.toml.
+	#[cfg(feature = "test-rng")]
+	type Randomness = substratekitties::TestRandomness<Runtime>;
+	/// No account controls another one until an identity or proxy module is wired in.
+	type IsSelfControlled = ();
+	/// Nothing reacts to kitty ownership changes yet.
+	type OnKittyTransferred = ();
+	/// Mix child DNA with the same hasher the system uses for ids.
+	type DnaHashing = BlakeTwo256;
 }
 
 construct_runtime!(
@@ -194,7 +209,7 @@ construct_runtime!(
 		Sudo: sudo,
 
         // Add my first substratekitties module.
-        Substratekitties: substratekitties::{Module, Call, Storage, Event<T>},
+        Substratekitties: substratekitties::{Module, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -215,6 +230,69 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Nonce, Call>;
 /// Executive: handles dispatch to the various modules.
 pub type Executive = executive::Executive<Runtime, Block, Context, Balances, AllModules>;
 
+decl_runtime_apis! {
+	/// Read-only queries over the substratekitties module for frontends.
+	///
+	/// Every enumeration is paged through `start`/`limit` and reads at most one page of
+	/// entries (capped by the module's `MAX_PAGE_SIZE`), so no call costs O(total supply).
+	/// Kitty ids are passed as `KittyId` so they can't be mixed up with DNA hashes.
+	pub trait KittiesApi {
+		/// Liked kitties with global indices in `[start, start + limit)` and their like counts, most liked first.
+		fn most_liked(start: u64, limit: u64) -> Vec<(Hash, u32)>;
+		/// Listed kitties with for-sale indices in `[start, start + limit)` and their prices.
+		fn marketplace(start: u64, limit: u64) -> Vec<(Hash, Balance)>;
+		/// Kitties with indices in `[start, start + limit)` of `owner`'s list.
+		fn owned_kitties_paged(owner: AccountId, start: u64, limit: u64) -> Vec<(Hash, substratekitties::Kitty<Hash, Balance, BlockNumber>)>;
+		/// Up to `limit` of the most recently created kitties, newest first.
+		fn recent_kitties(limit: u64) -> Vec<(Hash, substratekitties::Kitty<Hash, Balance, BlockNumber>)>;
+		/// The spotlighted kitty of the current `SpotlightPeriod`, if any kitties exist.
+		fn kitty_of_the_day() -> Option<Hash>;
+		/// The nonce the next kitty creation will hash into its id. The id is rehashed if it hits a burned kitty's id.
+		fn current_nonce() -> u64;
+		/// The kitty with the given id, or `None` if it doesn't exist or its stored entry is inconsistent.
+		fn get_kitty(kitty_id: substratekitties::KittyId<Hash>) -> Option<substratekitties::Kitty<Hash, Balance, BlockNumber>>;
+		/// The kitties with the given ids, in input order, with `None` for ids that don't exist.
+		/// Only the first `MAX_PAGE_SIZE` ids are read.
+		fn kitties_by_ids(kitty_ids: Vec<substratekitties::KittyId<Hash>>) -> Vec<Option<substratekitties::Kitty<Hash, Balance, BlockNumber>>>;
+		/// A display card for the kitty (owner, likes and the community name of its DNA), or `None` if it doesn't exist.
+		fn kitty_card(kitty_id: substratekitties::KittyId<Hash>) -> Option<substratekitties::KittyCard<AccountId, Hash, Balance, BlockNumber>>;
+		/// Totals over the kitties `who` owns. Only the first `MAX_PAGE_SIZE` are aggregated; `truncated` says when more exist.
+		fn account_summary(who: AccountId) -> substratekitties::AccountSummary<Balance>;
+		/// The summed price of the listed kitties with indices in `[start, start + limit)` of `who`'s list.
+		fn portfolio_value(who: AccountId, start: u64, limit: u64) -> Balance;
+		/// Chain-wide totals: supply, gen 0 and bred counts, listings, floor price and highest generation.
+		fn collection_stats() -> substratekitties::CollectionStats<Balance>;
+		/// A rough estimate of the storage bytes used by the kitties, from the per-kitty entries times the supply.
+		fn estimated_storage_bytes() -> u64;
+		/// The last recorded sales of a kitty as `(price, block)`, oldest first.
+		fn price_history(kitty_id: substratekitties::KittyId<Hash>) -> Vec<(Balance, BlockNumber)>;
+		/// Kitties sharing at least one parent with the given kitty, from entries `[start, start + limit)` of its parents' child lists.
+		/// Burned kitties and duplicates are skipped, so a page may be short. Gen-0 kitties have no siblings.
+		fn siblings_of(kitty_id: substratekitties::KittyId<Hash>, start: u64, limit: u64) -> Vec<Hash>;
+		/// Kitties with exactly this DNA. At most `MAX_KITTIES_PER_DNA` are indexed per DNA.
+		fn kitties_with_dna(dna: Hash) -> Vec<Hash>;
+		/// Parent-to-child edges found by walking down from `root`, visiting at most `max_nodes` kitties (capped at `MAX_PAGE_SIZE`).
+		fn lineage_edges(root: substratekitties::KittyId<Hash>, max_nodes: u32) -> Vec<(Hash, Hash)>;
+		/// Whether a kitty is unknown, idle or listed for sale (with its price).
+		fn sale_status(kitty_id: substratekitties::KittyId<Hash>) -> substratekitties::SaleStatus<Balance>;
+		/// Whether the kitty is for sale and `who` can pay its price while keeping the existential deposit.
+		fn can_afford(who: AccountId, kitty_id: substratekitties::KittyId<Hash>) -> bool;
+		/// Kitties with global indices in `[start, start + limit)` that have every trait bit in `required`.
+		fn find_by_traits(required: u64, start: u64, limit: u64) -> Vec<Hash>;
+		/// The cheapest kitty with for-sale index in `[start, start + limit)` that has every trait bit in `required`, with its price.
+		fn cheapest_for_sale(required: u64, start: u64, limit: u64) -> Option<(Hash, Balance)>;
+		/// The eight render parameters (body, eyes, mouth, color 1, color 2, pattern, accessory, background) for a DNA.
+		/// See `Substratekitties::portrait_seed` for the range of each one.
+		fn portrait_seed(dna: Hash) -> [u32; 8];
+		/// Runs `actions` as `who` against the current state and returns the index and error message of the first one that fails.
+		/// Nothing is committed. Only the first `MAX_PAGE_SIZE` actions are tried.
+		fn validate_sequence(
+			who: AccountId,
+			actions: Vec<substratekitties::Action<AccountId, Hash, Balance>>
+		) -> Result<(), (u32, Vec<u8>)>;
+	}
+}
+
 // Implement our runtime API endpoints. This is just a bunch of proxying.
 impl_runtime_apis! {
 	impl runtime_api::Core<Block> for Runtime {
@@ -274,4 +352,101 @@ impl_runtime_apis! {
 			Aura::slot_duration()
 		}
 	}
+
+	impl self::KittiesApi<Block> for Runtime {
+		fn most_liked(start: u64, limit: u64) -> Vec<(Hash, u32)> {
+			Substratekitties::most_liked(start, limit)
+		}
+
+		fn marketplace(start: u64, limit: u64) -> Vec<(Hash, Balance)> {
+			Substratekitties::marketplace(start, limit)
+		}
+
+		fn owned_kitties_paged(owner: AccountId, start: u64, limit: u64) -> Vec<(Hash, substratekitties::Kitty<Hash, Balance, BlockNumber>)> {
+			Substratekitties::owned_kitties_paged(owner, start, limit)
+		}
+
+		fn recent_kitties(limit: u64) -> Vec<(Hash, substratekitties::Kitty<Hash, Balance, BlockNumber>)> {
+			Substratekitties::recent_kitties(limit)
+		}
+
+		fn kitty_of_the_day() -> Option<Hash> {
+			Substratekitties::kitty_of_the_day()
+		}
+
+		fn current_nonce() -> u64 {
+			Substratekitties::current_nonce()
+		}
+
+		fn get_kitty(kitty_id: substratekitties::KittyId<Hash>) -> Option<substratekitties::Kitty<Hash, Balance, BlockNumber>> {
+			Substratekitties::get_kitty(*kitty_id)
+		}
+
+		fn kitties_by_ids(kitty_ids: Vec<substratekitties::KittyId<Hash>>) -> Vec<Option<substratekitties::Kitty<Hash, Balance, BlockNumber>>> {
+			Substratekitties::kitties_by_ids(kitty_ids.into_iter().map(|kitty_id| *kitty_id).collect())
+		}
+
+		fn kitty_card(kitty_id: substratekitties::KittyId<Hash>) -> Option<substratekitties::KittyCard<AccountId, Hash, Balance, BlockNumber>> {
+			Substratekitties::kitty_card(*kitty_id)
+		}
+
+		fn account_summary(who: AccountId) -> substratekitties::AccountSummary<Balance> {
+			Substratekitties::account_summary(who)
+		}
+
+		fn portfolio_value(who: AccountId, start: u64, limit: u64) -> Balance {
+			Substratekitties::portfolio_value(who, start, limit)
+		}
+
+		fn collection_stats() -> substratekitties::CollectionStats<Balance> {
+			Substratekitties::collection_stats()
+		}
+
+		fn estimated_storage_bytes() -> u64 {
+			Substratekitties::estimated_storage_bytes()
+		}
+
+		fn price_history(kitty_id: substratekitties::KittyId<Hash>) -> Vec<(Balance, BlockNumber)> {
+			Substratekitties::price_history(*kitty_id)
+		}
+
+		fn siblings_of(kitty_id: substratekitties::KittyId<Hash>, start: u64, limit: u64) -> Vec<Hash> {
+			Substratekitties::siblings_of(*kitty_id, start, limit)
+		}
+
+		fn kitties_with_dna(dna: Hash) -> Vec<Hash> {
+			Substratekitties::kitties_with_dna(dna)
+		}
+
+		fn lineage_edges(root: substratekitties::KittyId<Hash>, max_nodes: u32) -> Vec<(Hash, Hash)> {
+			Substratekitties::lineage_edges(*root, max_nodes)
+		}
+
+		fn sale_status(kitty_id: substratekitties::KittyId<Hash>) -> substratekitties::SaleStatus<Balance> {
+			Substratekitties::sale_status(*kitty_id)
+		}
+
+		fn can_afford(who: AccountId, kitty_id: substratekitties::KittyId<Hash>) -> bool {
+			Substratekitties::can_afford(who, *kitty_id)
+		}
+
+		fn find_by_traits(required: u64, start: u64, limit: u64) -> Vec<Hash> {
+			Substratekitties::find_by_traits(required, start, limit)
+		}
+
+		fn cheapest_for_sale(required: u64, start: u64, limit: u64) -> Option<(Hash, Balance)> {
+			Substratekitties::cheapest_for_sale(required, start, limit)
+		}
+
+		fn portrait_seed(dna: Hash) -> [u32; 8] {
+			Substratekitties::portrait_seed(dna)
+		}
+
+		fn validate_sequence(
+			who: AccountId,
+			actions: Vec<substratekitties::Action<AccountId, Hash, Balance>>
+		) -> Result<(), (u32, Vec<u8>)> {
+			Substratekitties::validate_sequence(who, actions)
+		}
+	}
 }
@@ -1,8 +1,10 @@
 use parity_codec::Encode;
 use system::ensure_signed;
 use support::{decl_storage, decl_module, StorageValue, StorageMap, dispatch::Result, ensure, decl_event};
-use runtime_primitives::traits::{As, Hash, Zero};
+use runtime_primitives::traits::{As, Hash, Zero, CheckedAdd, CheckedSub, CheckedMul, Saturating};
+use rstd::prelude::*;
 use rstd::cmp;
+use rstd::ops::Deref;
 
 // Substrateでは「あるトランザクションがFinalizeされたことが、直接そのトランザクションによって実行される
 // 関数が成功裏に終わったこと」を意味しない。Substrateでは「呼び出された関数が成功裏に終わったこと」を
@@ -30,16 +32,244 @@ use rstd::cmp;
 
 // kittyの所有権の変更はSwap and Popメソッドで行う。
 
+// 整数演算の方針：ストレージから読んだ値に対する演算は、必ずchecked_*を使って失敗をエラーとして返す。
+// overflowしても問題ない箇所でだけsaturating_*を使い、その理由をコメントに書く。
+// 素の+, -, *は、直前の確認で範囲に収まることが分かっている箇所でだけ使う。
+
 pub trait Trait: balances::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    // kittyのidやdnaの元になる乱数の供給源。より良い乱数を提供するモジュールに差し替えられる。
+    type Randomness: Randomness<Self::Hash>;
+    // あるアカウントが別のアカウントも管理しているかを確かめる仕組み。identityやproxyのモジュールにつなぐ。
+    type IsSelfControlled: SelfControlCheck<Self::AccountId>;
+    // kittyの所有者が変わるたびに呼ばれるフック。報酬などを扱う他のモジュールが所有者の変化に反応できる。
+    type OnKittyTransferred: OnKittyTransferred<Self::AccountId, Self::Hash>;
+    // 子供のdnaを混ぜるための乱数を計算するハッシュ関数。idを計算するsystemのHashingとは別に選べる。
+    type DnaHashing: Hash<Output = Self::Hash>;
+}
+
+// 乱数の供給源を抽象化するトレイト。
+pub trait Randomness<Hash> {
+    fn random_seed() -> Hash;
+}
+
+// デフォルトの供給源としてsystemモジュールのrandom_seedを使えるようにする。
+impl<T: system::Trait> Randomness<T::Hash> for system::Module<T> {
+    fn random_seed() -> T::Hash {
+        <system::Module<T>>::random_seed()
+    }
+}
+
+// テスト用の決定的な供給源。`test-rng`フィーチャーを有効にしたときだけ使える。
+// ブロックのrandom_seedの代わりにNonceから乱数を作るので、同じ操作を繰り返せば同じidとdnaが得られる。
+// 本番のチェーンでは絶対に使わないこと。
+#[cfg(feature = "test-rng")]
+pub struct TestRandomness<T>(rstd::marker::PhantomData<T>);
+
+#[cfg(feature = "test-rng")]
+impl<T: Trait> Randomness<T::Hash> for TestRandomness<T> {
+    fn random_seed() -> T::Hash {
+        (b"substratekitties/test-rng", <Nonce<T>>::get()).using_encoded(<T as system::Trait>::Hashing::hash)
+    }
+}
+
+// `who`が`other`のアカウントも管理しているかを確かめるトレイト。
+pub trait SelfControlCheck<AccountId> {
+    fn is_self_controlled(who: &AccountId, other: &AccountId) -> bool;
+}
+
+// デフォルトでは、どのアカウントも他のアカウントを管理していないものとする。
+impl<AccountId> SelfControlCheck<AccountId> for () {
+    fn is_self_controlled(_who: &AccountId, _other: &AccountId) -> bool {
+        false
+    }
+}
+
+// kittyの所有者が変わったことを受け取るトレイト。転送、売却、引き取りなど、どの経路で変わっても一度だけ呼ばれる。
+pub trait OnKittyTransferred<AccountId, Hash> {
+    fn on_transferred(from: &AccountId, to: &AccountId, kitty_id: &Hash);
+}
+
+// デフォルトでは何もしない。
+impl<AccountId, Hash> OnKittyTransferred<AccountId, Hash> for () {
+    fn on_transferred(_from: &AccountId, _to: &AccountId, _kitty_id: &Hash) {}
+}
+
+// チェーンのトークンの小数点以下の桁数。価格を人が読める形に整形するときに使う。
+pub const TOKEN_DECIMALS: usize = 15;
+
+// 列挙系の読み出し関数が一回で読む要素数の上限。全kittiesの数に関係なく、一回の呼び出しの計算量をこれで抑える。
+const MAX_PAGE_SIZE: u64 = 100;
+
+// 繁殖のクールダウンに掛ける世代係数の上限。
+const MAX_COOLDOWN_FACTOR: u64 = 10;
+
+// ジェネシスで配るkittyに設定できる世代の上限。
+const MAX_GENESIS_GENERATION: u64 = 100;
+
+// display_genで表示する世代の上限。
+const MAX_DISPLAY_GEN: u32 = 255;
+
+// 焼却済みのidに当たったときにハッシュし直す回数の上限。
+const MAX_REHASH_ATTEMPTS: u32 = 8;
+
+// kittyのidを表す型。dnaも同じHash型なので、呼び出し側で取り違えないようにdispatchableの引数はこの型で受け取る。
+// runtime APIとActionのkitty idもこの型である。ストレージのキーは中身のHashのままである。
+//
+// 取り違えがコンパイルで弾かれることは、次の二つのdoctestで確かめる。
+/// ```
+/// use node_template_runtime::{Hash, AccountId, Balance, substratekitties::{Action, KittyId}};
+/// let kitty_id = KittyId::from(Hash::default());
+/// let _: Action<AccountId, Hash, Balance> = Action::SetPrice(kitty_id, 1);
+/// ```
+///
+/// ```compile_fail
+/// use node_template_runtime::{Hash, AccountId, Balance, substratekitties::Action};
+/// let dna = Hash::default();
+/// let _: Action<AccountId, Hash, Balance> = Action::SetPrice(dna, 1);
+/// ```
+#[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct KittyId<Hash>(pub Hash);
+
+impl<Hash> Deref for KittyId<Hash> {
+    type Target = Hash;
+
+    fn deref(&self) -> &Hash {
+        &self.0
+    }
+}
+
+impl<Hash> From<Hash> for KittyId<Hash> {
+    fn from(hash: Hash) -> Self {
+        KittyId(hash)
+    }
+}
+
+// KittyとKittyCardのフィールドの順序は、そのままSCALEのエンコードとJSONのキーの順序になる。
+// クライアントがキャッシュしたり差分を取ったりできるように、並べ替えたり途中に挟んだりせず、新しいフィールドは必ず末尾に加える。
+// kittiy.jsonの型定義も同じ順序に保つ。
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize))]
+pub struct Kitty<Hash, Balance, BlockNumber> {
+    id: Hash,                  // idでkittyを唯一に識別する。
+    dna: Hash,                 // 個体に固有の値なのでdnaとして機能する。
+    price: Balance,            // 価格
+    gen: u64,                  // 性別。gender。
+    cooldown_end: BlockNumber, // このブロックになるまで繁殖できない。
+    soulbound: bool,           // trueならば譲渡も売買もできない。
+    trait_flags: u64,          // dnaから計算した特徴のビットマスク。絞り込み検索に使う。
+    minted_at: BlockNumber,    // 生まれたブロック
+    parents: Option<(Hash, Hash)>, // 両親のid。gen 0のkittyには親がいない。
+    last_fed_at: BlockNumber,  // 最後にご飯をもらったブロック。生まれたときにも満腹になる。
+    last_active_at: BlockNumber, // 最後に生まれたり、所有者が変わったり、繁殖したり、priceが変わったり、ご飯をもらったりしたブロック
+}
+
+// kittyのhappinessの最大値。ご飯をもらうとこの値に戻り、時間とともに減っていく。
+pub const MAX_HAPPINESS: u8 = 100;
+
+// 珍しいdnaにつける表示名の最大長（バイト）。
+const MAX_DNA_NAME_LENGTH: usize = 32;
+
+// コレクションの名前の最大長（バイト）。
+const MAX_COLLECTION_NAME_LENGTH: usize = 64;
+
+// DnaToKittiesに記録する、同じdnaを持つkittyの数の上限。これを超えたkittyは索引に載らない。
+const MAX_KITTIES_PER_DNA: usize = 16;
+
+// 出品に添える通貨の表示用ヒントの最大長（バイト）。
+const MAX_CURRENCY_HINT_LENGTH: usize = 32;
+
+// transfer_with_memoに添えられるmemoの最大長（バイト）。
+const MAX_MEMO_LENGTH: usize = 64;
+
+// 一匹のkittyにつけられるタグの数と、タグ一つの最大長（バイト）。
+const MAX_TAGS_PER_KITTY: usize = 8;
+const MAX_TAG_LENGTH: usize = 32;
+
+// 一つのレシピで焼却できるkittyの数の上限。
+const MAX_RECIPE_INPUTS: u32 = 10;
+
+// 所有者が自分のkittyをまとめて表示するための、名前付きのコレクション。
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Collection<AccountId> {
+    owner: AccountId, // コレクションの所有者
+    name: Vec<u8>,    // 表示名
+}
+
+// 管理者が定義する、kittyを焼却して新しいkittyを一匹作るレシピ。
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Recipe {
+    input_count: u32,   // 焼却するkittyの数
+    input_tier: u32,    // 焼却するkittyが全て持っていなければならない珍しさの段階（rarity_tier）
+    output_traits: u64, // 作られるkittyが必ず持つ特徴のビットマスク
 }
 
+// UIでkittyを一枚のカードとして表示するために、kittyとその周辺の情報をまとめたもの。フィールドの順序はKittyと同じ決まりに従う。
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize))]
+pub struct KittyCard<AccountId, Hash, Balance, BlockNumber> {
+    kitty: Kitty<Hash, Balance, BlockNumber>, // kitty本体
+    owner: AccountId,                         // 所有者
+    likes: u32,                               // likeの数
+    dna_name: Vec<u8>,                        // dnaにつけられた表示名。なければ空。
+    happiness: u8,                            // 今のブロックでのhappiness
+    tags: Vec<Vec<u8>>,                       // 所有者がつけたタグ
+    currency_hint: Vec<u8>,                   // 出品に添えられた通貨の表示用ヒント。なければ空。
+}
+
+// ウォレットのダッシュボード向けに、アカウントが所有するkittiesを集計したもの。
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AccountSummary<Balance> {
+    owned: u64,            // 所有しているkittyの数
+    listed: u64,           // そのうち売りに出しているkittyの数
+    listed_value: Balance, // 売りに出しているkittyの価格の合計
+    highest_gen: u64,      // 所有しているkittyの最大の世代
+    truncated: bool,       // trueならば、kittyが多すぎて先頭のMAX_PAGE_SIZE匹だけを集計した。
+}
+
+// 統計ページ向けに、チェーン全体のkittiesを集計したもの。
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct Kitty<Hash, Balance> {
-    id: Hash,       // idでkittyを唯一に識別する。
-    dna: Hash,      // 個体に固有の値なのでdnaとして機能する。
-    price: Balance, // 価格
-    gen: u64,       // 性別。gender。
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CollectionStats<Balance> {
+    total_supply: u64,             // 今いるkittyの数
+    gen_zero_count: u64,           // これまでに生まれたgen 0のkittyの数。ジェネシスで配ったkittyは含まない。
+    bred_count: u64,               // これまでに繁殖で生まれたkittyの数。ジェネシスで配ったkittyは含まない。
+    genesis_count: u64,            // ジェネシスで配ったkittyの数
+    for_sale_count: u64,           // 売りに出ているkittyの数
+    floor_price: Option<Balance>,  // 売りに出ているkittyの最安値。出品がなければNone。
+    highest_gen: u64,              // これまでに生まれたkittyの最大の世代
+}
+
+// RecentEventsに残す、kittyのイベントの要約。
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum EventSummary<AccountId, Hash, Balance> {
+    Minted(AccountId, Hash),                 // `AccountId`に`Hash`のkittyが生まれた。
+    Transferred(AccountId, AccountId, Hash), // 一つ目の`AccountId`から二つ目の`AccountId`に`Hash`のkittyが移った。
+    Sold(Hash, Balance),                     // `Hash`のkittyが`Balance`で売れた。
+}
+
+// validate_sequenceで試しに実行できる操作。それぞれ同じ名前のdispatchableに対応する。
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Action<AccountId, Hash, Balance> {
+    Create,                                   // create_kitty
+    Breed(KittyId<Hash>, KittyId<Hash>),      // breed_kitty
+    SetPrice(KittyId<Hash>, Balance),         // set_price
+    Transfer(AccountId, KittyId<Hash>),       // transfer
+}
+
+// kittyが今売りに出ているかどうか。UIが一回の呼び出しで状態を知るために使う。
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum SaleStatus<Balance> {
+    Unknown,         // そのkittyは存在しない。
+    Idle,            // 売りに出ていない。
+    Listed(Balance), // `Balance`で売りに出ている。
 }
 
 // decl_eventマクロの適用によってブロックチェーンの状態遷移後に返されるイベントの型を定義する。
@@ -47,12 +277,43 @@ decl_event!(
     pub enum Event<T>
         where <T as system::Trait>::AccountId,
               <T as system::Trait>::Hash,
+              <T as system::Trait>::BlockNumber,
               <T as balances::Trait>::Balance
     {
         Created(AccountId, Hash),                // `AccountId`が`Hash`で指し示されるkittyをcreateした。
         PriceSet(AccountId, Hash, Balance),      // `AccountId`が`Hash`で指し示されるkittyのpriceを`Balance`に設定した。
         Transferred(AccountId, AccountId, Hash), // `AccountId`が`AccountId`に`Hash`で指し示されるkittyをtransferした。
+        TransferredWithMemo(AccountId, AccountId, Hash, Vec<u8>), // Transferredと同じ転送に、送り手が`Vec<u8>`のmemoを添えた。
         Bought(AccountId, AccountId, Hash, Balance),   // `AccountId`が`AccountId`から`Hash`で指し示されるkittyを`Balance`buyした。
+        ListingExpired(Hash, BlockNumber),       // `Hash`で指し示されるkittyの出品が`BlockNumber`で期限切れになった。
+        Liked(AccountId, Hash),                  // `AccountId`が`Hash`で指し示されるkittyをlikeした。
+        Unliked(AccountId, Hash),                // `AccountId`が`Hash`で指し示されるkittyのlikeを取り消した。
+        CollectionCreated(AccountId, u32),       // `AccountId`が`u32`で指し示されるコレクションを作った。
+        KittyAddedToCollection(u32, Hash),       // `u32`で指し示されるコレクションに`Hash`で指し示されるkittyを加えた。
+        KittyRemovedFromCollection(u32, Hash),   // `u32`で指し示されるコレクションから`Hash`で指し示されるkittyを外した。
+        BreedRequested(AccountId, Hash, Hash, Balance), // `AccountId`が自分の`Hash`と相手の`Hash`の繁殖を手数料`Balance`で申し込んだ。
+        BreedApproved(AccountId, Hash, Hash),    // `AccountId`が自分の`Hash`と相手の`Hash`の繁殖を承認した。
+        DnaNameSet(Hash),                        // `Hash`のdnaに表示名がつけられた（空ならば外された）。
+        Claimed(AccountId, Hash),                // `AccountId`が持ち主のいなくなった`Hash`で指し示されるkittyを引き取った。
+        Burned(AccountId, Hash),                 // `AccountId`が`Hash`で指し示されるkittyを焼却した。
+        Bred(AccountId, Hash, Hash, Hash),       // `AccountId`の繁殖で、両親`Hash`と`Hash`から子供`Hash`が生まれた。（子供, 親1, 親2の順）
+        BredForGift(AccountId, AccountId, Hash), // 一つ目の`AccountId`が繁殖させた子供`Hash`が、二つ目の`AccountId`に贈られた。
+        BreedFailed(AccountId, Hash, Hash),      // `AccountId`による`Hash`と`Hash`の繁殖が失敗し、子供は生まれなかった。
+        ApprovalForAll(AccountId, AccountId, bool), // 一つ目の`AccountId`が二つ目の`AccountId`をoperatorに設定した（falseならば取り消した）。
+        SupplyThresholdReached(u64),             // kittyの総数が警告の閾値に達した。あと`u64`匹で上限に達する。
+        AdminChanged(Option<AccountId>),         // 管理者が`Option<AccountId>`に替わった（Noneならば管理者がいなくなった）。
+        CoOwnerAdded(Hash, AccountId),           // `Hash`で指し示されるkittyに共同所有者`AccountId`が加わった。
+        CoOwnerRemoved(Hash, AccountId),         // `Hash`で指し示されるkittyから共同所有者`AccountId`が外れた。
+        CoOwnerApproval(AccountId, Hash, bool),  // 共同所有者`AccountId`が`Hash`で指し示されるkittyの転送や売却を承認した（falseならば取り消した）。
+        BreedingPausedUntil(BlockNumber),        // `BlockNumber`になるまで繁殖が止められた。今のブロック以前ならば止めていた繁殖が再開された。
+        Fed(AccountId, Hash),                    // `AccountId`が`Hash`で指し示されるkittyにご飯をあげた。
+        RecipeSet(u32),                          // `u32`のレシピが定義された。
+        RecipeRemoved(u32),                      // `u32`のレシピが取り除かれた。
+        Crafted(AccountId, u32, Hash),           // `AccountId`が`u32`のレシピでkittyを焼却し、`Hash`で指し示されるkittyを作った。
+        PutUpForAdoption(AccountId, Hash),       // `AccountId`が`Hash`で指し示されるkittyを里親募集に出した。
+        Adopted(AccountId, Hash),                // `AccountId`が里親募集中の`Hash`で指し示されるkittyを引き取った。
+        GenesisKittiesCreated(u64),              // ジェネシスで`u64`匹のkittyが配られた。最初のブロックで一度だけ吐く。
+        StateSnapshot(u64, AccountId, u64),      // 状態遷移後の全kittiesの個体数、`AccountId`、その所有数。EmitSnapshotsが有効なときだけ吐く。
     }
 );
 
@@ -62,12 +323,15 @@ decl_storage! {
         // Declare storage and its getter functions here.
 
         // hash value is a unique key to each kitty.
-        Kitties get(kitty): map T::Hash => Kitty<T::Hash, T::Balance>; // hash value => kitty
+        Kitties get(kitty): map T::Hash => Kitty<T::Hash, T::Balance, T::BlockNumber>; // hash value => kitty
         KittyOwner get(owner_of): map T::Hash => Option<T::AccountId>; // hash value => account ID
 
         AllKittiesArray get(kitty_by_index): map u64 => T::Hash;       // kitty's index => hash value
         AllKittiesCount get(all_kitties_count): u64;                   // how many kitties exist?
         AllKittiesIndex: map T::Hash => u64;                           // hash value => kitty's index
+        // 生まれた順のkittyの記録。AllKittiesArrayは焼却で詰められて順番が崩れるので、こちらは追記するだけで詰めない。
+        CreationLog get(created_by_index): map u64 => T::Hash;         // 生まれた順の通し番号 => hash value
+        CreationCount get(creation_count): u64;                        // これまでに生まれたkittyの数（焼却されたものも含む）
 
         // OwnedKitty get(kitty_of_owner): map T::AccountId => T::Hash;   // account ID => hash value
         OwnedKittiesArray get(kitty_of_owner_by_index): map (T::AccountId, u64) => T::Hash; // (account ID, the index of owned kitty) => hash value
@@ -75,6 +339,208 @@ decl_storage! {
         OwnedKittiesIndex: map T::Hash => u64; // そのkittyが所有者にとって何番目のkittyなのかを返す。
 
         Nonce: u64;
+
+        // 売りに出ているkittyのリスト。AllKittiesArrayと同じようにマッピングでリストをエミュレートする。
+        ForSaleArray get(for_sale_by_index): map u64 => T::Hash;       // 出品中のkittyの通し番号 => hash value
+        ForSaleCount get(for_sale_count): u64;                         // 出品中のkittyの数
+        ForSaleIndex: map T::Hash => u64;                              // hash value => 出品中のkittyの通し番号
+
+        // 出品の有効期間（ブロック数）。0ならば出品は期限切れにならない。
+        ListingDuration get(listing_duration) config(): T::BlockNumber;
+        ListingExpiry get(listing_expiry): map T::Hash => Option<T::BlockNumber>; // hash value => 出品が期限切れになるブロック
+        // block number => そのブロックで期限切れになる出品のリスト。
+        // on_finaliseでは該当するブロックのバケツだけを処理するので、全kittiesを走査する必要がない。
+        ExpiringAt get(expiring_at): map T::BlockNumber => Vec<T::Hash>;
+
+        // 繁殖後のクールダウンの基本値（ブロック数）。実際のクールダウンは世代に応じて伸びる。
+        BreedingCooldown get(breeding_cooldown) config(): T::BlockNumber;
+        // trueならば、gen 0のkittyにはクールダウンがない。
+        GenZeroExemptFromCooldown get(gen_zero_exempt_from_cooldown) config(): bool;
+
+        Likes get(likes_of): map T::Hash => u32;                            // hash value => likeの数
+        HasLiked get(has_liked): map (T::Hash, T::AccountId) => bool;       // (hash value, account ID) => likeしたかどうか
+
+        KittyMinter get(minter_of): map T::Hash => Option<T::AccountId>;    // hash value => そのkittyを生み出したアカウント
+        // 売却額のうちminterに支払うロイヤリティと、焼却する分の割合（%）。合計は100以下でなければならない。
+        RoyaltyPercent get(royalty_percent) config(): u32;
+        BurnPercent get(burn_percent) config(): u32;
+        // trueならば、買取側の残高がexistential depositを下回るような購入を拒む。
+        // falseならばそのような購入も通し、残高の尽きた買取側のアカウントは消える（購入したkittyは所有者のいないものとして残る）。
+        BuyerKeepAlive get(buyer_keep_alive) config(): bool = true;
+
+        StarterPackSize get(starter_pack_size) config(): u32;               // スターターパックで配るkittyの数
+        ClaimedStarter get(claimed_starter): map T::AccountId => bool;      // account ID => スターターパックを受け取ったかどうか
+
+        // アカウントごとの、一定期間あたりの繁殖回数の上限。0ならば上限なし。
+        MaxBreedsPerPeriod get(max_breeds_per_period) config(): u32;
+        BreedPeriod get(breed_period) config(): T::BlockNumber;
+        BreedsToday get(breeds_today): map T::AccountId => (T::BlockNumber, u32); // account ID => (期間の開始ブロック, その期間の繁殖回数)
+
+        Collections get(collection): map u32 => Option<Collection<T::AccountId>>; // collection ID => collection
+        NextCollectionId get(next_collection_id): u32;                      // 次に作られるコレクションのID
+        KittyCollection get(collection_of): map T::Hash => Option<u32>;     // hash value => そのkittyが入っているコレクション（一つまで）
+
+        // kittyごとに残しておく売却履歴の件数。古いものから捨てる。
+        PriceHistoryLength get(price_history_length) config(): u32;
+        PriceHistory get(price_history): map T::Hash => Vec<(T::Balance, T::BlockNumber)>; // hash value => 古い順の(売却額, 売却されたブロック)
+
+        // 所有者のアカウントが消えてからこのブロック数が経ったkittyは、誰でも引き取れる。0ならば引き取れない。
+        // 残高のない所有者を消えたとみなすので、鍵を持っている所有者のkittyも引き取られうる。十分長くすること。
+        AbandonmentPeriod get(abandonment_period) config(): T::BlockNumber;
+
+        // trueならば、所有権が変わるたびにStateSnapshotイベントを吐く。インデクサのデバッグ用で、普段は無効にしておく。
+        EmitSnapshots get(emit_snapshots) config(): bool;
+
+        // 生まれてからこのブロック数が経つまでは繁殖できない。
+        MinBreedingAge get(min_breeding_age) config(): T::BlockNumber;
+
+        // trueならば、親を共有するkitty同士（兄弟姉妹）は繁殖できない。
+        ForbidSiblingBreeding get(forbid_sibling_breeding) config(): bool;
+
+        DnaNames get(dna_name): map T::Hash => Vec<u8>;                     // dna => コミュニティが決めた表示名
+
+        // そのkittyを親として生まれた子供のリスト。OwnedKittiesArrayと同じようにマッピングでリストをエミュレートする。
+        // 繁殖のたびにリスト全体を読み書きしないよう、Vecではなく通し番号で一匹ずつ記録する。追記するだけで詰めない。
+        ChildrenOf get(child_of_by_index): map (T::Hash, u64) => T::Hash;   // (親のhash value, 何番目の子供か) => 子供のhash value
+        ChildrenCount get(children_count): map T::Hash => u64;             // 親のhash value => これまでに生まれた子供の数
+
+        // (申し込んだ側のkitty, 相手のkitty) => (申し込んだアカウント, 予約してある手数料)
+        BreedRequests get(breed_request): map (T::Hash, T::Hash) => Option<(T::AccountId, T::Balance)>;
+
+        // 売買ではない無償の転送にかかる手数料と、その受取先。統計を操作するための転送の繰り返しを抑える。0ならば無料。
+        TransferFee get(transfer_fee) config(): T::Balance;
+        FeeCollector get(fee_collector) config(): T::AccountId;
+
+        // 繁殖の手数料。繁殖の確認が全て通ったときだけ焼却される。
+        BreedingFee get(breeding_fee) config(): T::Balance;
+        // 繁殖が失敗して子供が生まれない確率（1万分率）。失敗しても手数料は焼却され、クールダウンも回数も数える。0ならば必ず成功する。
+        BreedFailureChanceBps get(breed_failure_chance_bps) config(): u32;
+
+        // 生まれてからこのブロック数が経つまでは売りに出せない。
+        MintProtection get(mint_protection) config(): T::BlockNumber;
+
+        // 焼却されたkittyのid。同じidが二度と使われないように、焼却後も残しておく。
+        RetiredIds get(is_retired): map T::Hash => bool;
+
+        // 所有者が整理のためにkittyにつけたタグ。所有者ごとのものなので、転送すると消える。
+        KittyTags get(tags_of): map T::Hash => Vec<Vec<u8>>;
+
+        // これまでに生まれたgen 0のkittyの数と、繁殖で生まれたkittyの数。焼却しても減らない。
+        // ジェネシスで配ったkittyは世代によらずどちらにも数えず、GenesisCountに数える。
+        GenZeroCount get(gen_zero_count): u64;
+        BredCount get(bred_count): u64;
+        GenesisCount get(genesis_count): u64;
+        // gen 0のkittyとジェネシスで配ったkitty一匹あたりに繁殖で生まれてよいkittyの数。0ならば制限しない。
+        MaxBredPerGenZero get(max_bred_per_gen_zero) config(): u32;
+
+        // 一度の繁殖で生まれる子供の数。0は1として扱う。
+        LitterSize get(litter_size) config(): u32;
+
+        // 一つのブロックで生み出せるkittyの数。0ならば制限しない。
+        MaxMintsPerBlock get(max_mints_per_block) config(): u32;
+        // このブロックで生まれたkittyの数。ブロックの最初に0に戻る。
+        MintsThisBlock get(mints_this_block): u32;
+
+        // breed_many、transfer_all、cancel_all_listingsのように、一度の呼び出しで複数のkittyを扱う関数が扱えるkittyや組の数の上限。
+        // ブロックの重さを抑えるために、組の数や`limit`が上限を超えると、何もせずにBatchTooLargeで失敗する。
+        MaxBatchSize get(max_batch_size) config(): u32;
+
+        // (所有者, operator) => operatorが所有者の全てのkittyを管理してよいかどうか
+        OperatorApprovals get(is_approved_for_all): map (T::AccountId, T::AccountId) => bool;
+
+        // 出品中のkittyの最安値。出品がなければNone。FloorRescanがSomeの間は古い値なので使わない。
+        FloorPrice get(floor_price): Option<T::Balance>;
+        // 最安値の出品が取り下げられたり値上げされたりして、最安値を出品中のリストから求め直している途中ならばSome。
+        // (次に読む出品の通し番号, それまでに読んだ出品の最安値)。on_finaliseで一ブロックにMAX_PAGE_SIZE件ずつ読み進める。
+        FloorRescan get(floor_rescan): Option<(u64, Option<T::Balance>)>;
+
+        // rootの代わりに運用のための関数を呼べるアカウント。
+        Admin get(admin): Option<T::AccountId>;
+
+        // このブロックになるまで、全ての繁殖を止めておく。メンテナンスの時間を前もって決めておき、明けたら何もしなくても再開する。
+        BreedingPausedUntil get(breeding_paused_until): Option<T::BlockNumber>;
+
+        // hash value => 所有者と共にkittyを持つ共同所有者。転送や売却には共同所有者の承認がいるが、繁殖はどちらもできる。
+        CoOwner get(co_owner_of): map T::Hash => Option<T::AccountId>;
+        // hash value => 共同所有者が次の転送か売却を承認したかどうか。所有者が変わると消える。
+        CoOwnerApproval get(co_owner_approved): map T::Hash => bool;
+
+        // 最近のkittyのイベントの要約を古い順に、(ブロック, 要約)で残しておく。イベントを捨てるチェーンでも、後から来たインデクサーが最近の動きを拾える。
+        // ブロックの最初にRecentEventsLength件まで古いものから捨てる。0ならば何も残さない。
+        RecentEventsLength get(recent_events_length) config(): u32;
+        RecentEvents get(recent_events): Vec<(T::BlockNumber, EventSummary<T::AccountId, T::Hash, T::Balance>)>;
+
+        // これまでに生まれたkittyの最大の世代。焼却しても下がらない。
+        HighestGen get(highest_gen): u64;
+
+        // kittyの総数の上限。0ならば制限しない。ジェネシスで配るkittyには適用しない。
+        MaxTotalSupply get(max_total_supply) config(): u64;
+        // 総数が上限のこの割合（%）に達したらSupplyThresholdReachedイベントを吐く。
+        SupplyWarningPercent get(supply_warning_percent) config(): u32;
+        // SupplyThresholdReachedをもう吐いたかどうか。焼却で閾値を下回ると戻る。
+        SupplyWarned get(supply_warned): bool;
+
+        // dna => そのdnaを持つkittyのid。一つのdnaにつきMAX_KITTIES_PER_DNA匹までしか載せないので、それ以上は増えない。
+        DnaToKitties get(kitties_with_dna): map T::Hash => Vec<T::Hash>;
+
+        // 出品中のkittyに所有者が添えた、希望する通貨の表示用ヒント（例えば"USD-pegged"）。
+        // 支払いは常にこのチェーンのbalancesで行われ、このヒントは表示にしか使わない。出品が取り下げられると消える。
+        ListingCurrencyHint get(currency_hint_of): map T::Hash => Vec<u8>;
+
+        // happinessが1減るまでのブロック数。0ならばhappinessは減らない。
+        HappinessDecayPeriod get(happiness_decay_period) config(): T::BlockNumber;
+        // ご飯をあげるのにかかる手数料。FeeCollectorに支払われる。
+        FeedingFee get(feeding_fee) config(): T::Balance;
+        // 繁殖するのに両親が満たしていなければならないhappiness。0ならば確認しない。
+        MinBreedingHappiness get(min_breeding_happiness) config(): u8;
+
+        // 繁殖のときに変異したdnaのバイトが、親から受け継いだ値からずれる最大の幅。0ならば変異しない。
+        MaxMutationDelta get(max_mutation_delta) config(): u8;
+
+        // kitty_of_the_dayで選ぶkittyが替わるまでのブロック数。0ならば選ばない。
+        SpotlightPeriod get(spotlight_period) config(): T::BlockNumber;
+
+        Recipes get(recipe): map u32 => Option<Recipe>;                     // recipe ID => kittyを焼却して作るレシピ
+
+        // 里親募集中のkittyを預かるアカウント。誰も署名できないアカウントにしておく。
+        AdoptionCenter get(adoption_center) config(): T::AccountId;
+        // 里親募集中のkittyのキュー。AdoptionPoolHead番からAdoptionPoolCount匹が、預けられた順に並んでいる。
+        AdoptionPool get(adoption_pool_at): map u64 => T::Hash;             // 預けられた通し番号 => hash value
+        AdoptionPoolHead get(adoption_pool_head): u64;                      // 次に引き取られるkittyの通し番号
+        AdoptionPoolCount get(adoption_pool_count): u64;                    // 里親募集中のkittyの数
+
+        // GenesisKittiesCreatedイベントをもう吐いたかどうか。
+        GenesisSeeded get(genesis_seeded): bool;
+    }
+    add_extra_genesis {
+        // ジェネシスで配るkitty。(所有者, id兼dna, 世代)。最初のブロックでGenesisKittiesCreatedイベントとして数を知らせる。
+        config(kitties): Vec<(T::AccountId, T::Hash, u64)>;
+
+        build(|storage: &mut runtime_primitives::StorageOverlay, _: &mut runtime_primitives::ChildrenStorageOverlay, config: &GenesisConfig<T>| {
+            runtime_io::with_storage(storage, || {
+                for &(ref owner, kitty_id, gen) in &config.kitties {
+                    assert!(gen <= MAX_GENESIS_GENERATION, "genesis kitties must not be above the maximum genesis generation");
+
+                    let kitty = Kitty {
+                        id: kitty_id,
+                        dna: kitty_id,
+                        price: <T::Balance as As<u64>>::sa(0),
+                        gen,
+                        cooldown_end: <T::BlockNumber as As<u64>>::sa(0),
+                        soulbound: false,
+                        trait_flags: 0,
+                        minted_at: <T::BlockNumber as As<u64>>::sa(0),
+                        parents: None,
+                        last_fed_at: <T::BlockNumber as As<u64>>::sa(0),
+                        last_active_at: <T::BlockNumber as As<u64>>::sa(0),
+                    };
+
+                    // 全kittiesと所有者のリストを通常の生成と同じ経路で更新する。
+                    <Module<T>>::_mint(owner.clone(), kitty_id, kitty, true)
+                        .expect("genesis kitties must have unique ids");
+                }
+            });
+        });
     }
 }
 // decl_moduleマクロの適用によってチェーンに刻むデータへのアクセスインタフェースの実装を記述する。
@@ -85,6 +551,27 @@ decl_module! {
         // トランザクションの執行後にイベントを吐く関数をデフォルトの挙動で定義する。
         fn deposit_event<T>() = default;
 
+        // ブロックの最後に、このブロックで期限切れになる出品を取り下げ、出品中の最安値を求め直している途中ならば読み進める。
+        fn on_finalise(n: T::BlockNumber) {
+            Self::_expire_listings(n);
+            Self::_rescan_floor_price();
+        }
+
+        // ブロックの最初に、このブロックで生まれたkittyの数を数え直し、古いイベントの要約を捨てる。
+        fn on_initialise(_n: T::BlockNumber) {
+            <MintsThisBlock<T>>::kill();
+            Self::_trim_recent_events();
+
+            // ジェネシスではイベントを吐けないので、最初のブロックでジェネシスで配ったkittyの数を一度だけ知らせる。
+            if !Self::genesis_seeded() {
+                <GenesisSeeded<T>>::put(true);
+                let count = Self::genesis_count();
+                if count > 0 {
+                    Self::deposit_event(RawEvent::GenesisKittiesCreated(count));
+                }
+            }
+        }
+
         // 新しいKittyを生成し、その成否を返す関数を定義する。
         // Kittyたちはリストのような見た目のデータ構造でアカウントに紐づけられた形で管理される。
         fn create_kitty(origin) -> Result {
@@ -92,38 +579,44 @@ decl_module! {
             // Verify first, write lastの原則：create_kitty()を叩いたsenderの正当性を確認する。
             let sender = ensure_signed(origin)?;
 
-            // nonceを計算する。
-            let nonce = <Nonce<T>>::get();
+            // 新たに生成されたkittyを記録する。
+            Self::_create_kitty(sender, false)?;
 
-            // creat_kitty()を叩いたsenderからnonceと合わせてハッシュ値を計算する。
-            // 「random_hash <--> kitty」は一対一対応している。
-            let random_hash = (<system::Module<T>>::random_seed(), &sender, nonce)
-                .using_encoded(<T as system::Trait>::Hashing::hash);
+            Ok(())
+        }
 
-            // 計算したrandom_hashが衝突していないことを確認する。
-            ensure!(!<KittyOwner<T>>::exists(random_hash), "the kitty coressponding to this ID already exit!");
+        // gen 0のkittyを生成し、そのまま`price`で売りに出す関数。
+        fn create_and_list(origin, price: T::Balance) -> Result {
 
-            // new_kittyを生成する。
-            let new_kitty = Kitty {
-                id: random_hash,
-                dna: random_hash,
-                price: <T::Balance as As<u64>>::sa(0),
-                gen: 0,
-            };
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-            // 新たに生成されたkittyを記録する。
-            Self::_mint(sender, random_hash, new_kitty)?;
+            // price == 0では売りに出したことにならないので、その場合はcreate_kittyを使う。
+            ensure!(!price.is_zero(), "Error: the price must not be zero; use create_kitty to create a kitty without listing it");
 
-            // Nonceをインクリメント
-            <Nonce<T>>::mutate(|n| {
-                *n += 1
-            });
+            // 生まれたばかりのkittyを売りに出せない設定のチェーンでは使えない。
+            ensure!(Self::mint_protection().is_zero(), "Error: MintProtectionActive: new kitties can not be listed right after minting on this chain");
+
+            let kitty_id = Self::_create_kitty(sender.clone(), false)?;
+            Self::_set_price(sender, kitty_id, price)?;
+
+            Ok(())
+        }
+
+        // 譲渡できない（soulboundな）kittyを`to`に生成する関数。実績の報酬などに使うので、rootか管理者だけが呼べる。
+        fn create_soulbound_kitty(origin, to: T::AccountId) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            Self::_create_kitty(to, true)?;
 
             Ok(())
         }
 
         // kittyのIDと新しいpriceを与えて、kittyのpriceを更新する関数を定義する。
-        fn set_price(origin, kitty_id: T::Hash, new_price: T::Balance) -> Result {
+        fn set_price(origin, kitty_id: KittyId<T::Hash>, new_price: T::Balance) -> Result {
+            let kitty_id = *kitty_id;
 
             // Verify first, write lastの原則：create_kitty()を叩いたsenderの正当性を確認する。
             let sender = ensure_signed(origin)?;
@@ -131,237 +624,3246 @@ decl_module! {
             // Verify first, write lastの原則：指定したkittyが存在することを確認する。
             ensure!(<Kitties<T>>::exists(kitty_id), "Error: invalid kitty id: this kitty does not exist");
 
-            // Verify first, write lastの原則：本当にそのkittyはあなたのもの？
+            // Verify first, write lastの原則：本当にそのkittyはあなたのもの？（所有者から管理を任されたoperatorでもよい）
             let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?; // そもそも所有者のいないkittyだった。
-            ensure!(owner == sender, "Error: you have no ownership to this kitty"); // あなたのkittyではなかった。
+            ensure!(Self::_is_owner_or_operator(&owner, &sender), "Error: you have no ownership to this kitty"); // あなたのkittyではなかった。
 
-            // kittyをkitty IDで引き出して、priceを更新して、書き戻す。
-            let mut kitty = Self::kitty(kitty_id);
-            kitty.price = new_price;
-            <Kitties<T>>::insert(kitty_id, kitty);
+            // priceが変わらないならば、何も書き込まずに終わる。出品の有効期限も延びない。
+            let kitty = Self::kitty(kitty_id);
+            if kitty.price == new_price {
+                return Ok(());
+            }
+
+            // Verify first, write lastの原則：soulboundなkittyと、生まれたばかりのkittyは売りに出せない。
+            if !new_price.is_zero() {
+                ensure!(!kitty.soulbound, "Error: this kitty is soulbound and can not be listed for sale");
+                Self::_ensure_mint_protection_over(&kitty)?;
+            }
 
-            // ブロックチェーンの状態が遷移したので、それを通知するイベントを吐く。
-            Self::deposit_event(RawEvent::PriceSet(sender, kitty_id, new_price));
+            // priceを更新する。
+            Self::_set_price(owner, kitty_id, new_price)?;
 
             Ok(())
         }
 
-        // 呼び出し側が転送先を指定してkittyを転送し、その成否を返す関数を定義する。
-        fn transfer(origin, to: T::AccountId, kitty_id: T::Hash) -> Result {
+        // 呼び出し側の所有リストの通し番号`[start, start + limit)`にあるkittyのうち、売りに出しているものの出品を取り下げる関数。
+        // `limit`がMaxBatchSizeを超えると失敗するので、全ての出品を取り下げるにはページを進めて呼び直す。
+        // 出品の取り下げでは所有リストの順番は変わらない。取り下げたkittyごとにPriceSetイベントを吐く。
+        fn cancel_all_listings(origin, start: u64, limit: u64) -> Result {
 
             // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
             let sender = ensure_signed(origin)?;
 
-            // Verify first, write lastの原則：転送したいkittyの存在を確認する。
-            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
-            ensure!(owner == sender, "Error: you have no ownership for this kitty");
+            // Verify first, write lastの原則：一度に扱うkittyの数がMaxBatchSize以下であることを確認する。
+            Self::_ensure_batch_size(limit)?;
+            let listed: Vec<T::Hash> = Self::_page(start, limit, Self::owned_kitty_count(&sender))
+                .map(|i| Self::kitty_of_owner_by_index((sender.clone(), i)))
+                .filter(|kitty_id| Self::is_listed(&Self::kitty(kitty_id)))
+                .collect();
 
-            // 転送をする。
-            Self::_transfer_from(sender, to, kitty_id)?;
+            for kitty_id in listed {
+                Self::_set_price(sender.clone(), kitty_id, <T::Balance as As<u64>>::sa(0))?;
+            }
 
             Ok(())
         }
 
-        // 呼び出し側が買いたいkittyのIDと買取額を引数に与えて、購入を実行し、その成否を返す関数を定義する。
-        fn buy_kitty(origin, kitty_id: T::Hash, max_price: T::Balance) -> Result {
+        // 呼び出し側が転送先を指定してkittyを転送し、その成否を返す関数を定義する。
+        fn transfer(origin, to: T::AccountId, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
 
             // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
             let sender = ensure_signed(origin)?;
 
-            // Verify first, write lastの原則：買いたいkittyが存在することを確認する。
-            ensure!(<Kitties<T>>::exists(kitty_id), "Error: invalid kitty id: this kitty does not exist");
-
-            // Verify first, write lastの原則：kittyの所有者が正当であることを確認する。
-            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
-            ensure!(owner != sender, "Error: you can not buy your own kitty");
-
-            // 売買されるkittyを引き出す。
-            let mut kitty = Self::kitty(kitty_id);
-            // 売却額を確認する。
-            let kitty_price = kitty.price;
-
-            // 売却額 == 0のkittyは売却対象ではないものとする。
-            ensure!(!kitty_price.is_zero(), "Error: this kitty you want to buy is not for sale");
+            Self::_paid_transfer(sender, to, kitty_id)?;
 
-            // 買取側の口座残高が売却額以下でないと買えないので確認する。
-            ensure!(kitty_price <= max_price, "Error: this kitty you want to buy costs more than your max price");
+            Ok(())
+        }
 
-            // 双方の残高をアトミックに更新する。
-            <balances::Module<T>>::make_transfer(&sender, &owner, kitty_price)?;
+        // transferと同じようにkittyを転送し、`memo`をイベントに添える関数。memoはストレージには保存しない。
+        // 取引所などが、自分の管理番号を転送に結びつけるのに使う。
+        fn transfer_with_memo(origin, to: T::AccountId, kitty_id: KittyId<T::Hash>, memo: Vec<u8>) -> Result {
+            let kitty_id = *kitty_id;
 
-            // kittyを売却側から購入側へ転送する。
-            Self::_transfer_from(owner.clone(), sender.clone(), kitty_id)?;
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-            kitty.price = <T::Balance as As<u64>>::sa(0);
+            ensure!(memo.len() <= MAX_MEMO_LENGTH, "Error: the memo is too long");
 
-            <Kitties<T>>::insert(kitty_id, kitty);
+            let from = Self::_paid_transfer(sender, to.clone(), kitty_id)?;
 
-            // Boughtイベントを吐く。
-            Self::deposit_event(RawEvent::Bought(sender, owner, kitty_id, kitty_price));
+            Self::deposit_event(RawEvent::TransferredWithMemo(from, to, kitty_id, memo));
 
             Ok(())
         }
 
-        // 親となる二匹を引数として与えて、子供を作らせ、その成否を返す関数。
-        fn breed_kitty(origin, kitty_id_1: T::Hash, kitty_id_2: T::Hash) -> Result {
+        // 自分が管理している別のアカウント`to`にkittyを移す関数。同じ持ち主の中での移動なので転送の手数料はかからない。
+        // `to`を管理していることはIsSelfControlledで確かめる。
+        fn transfer_self_controlled(origin, to: T::AccountId, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
 
             // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
             let sender = ensure_signed(origin)?;
 
-            // Verify first, write lastの原則：kittyの存在確認。
-            ensure!(<Kitties<T>>::exists(kitty_id_1), "Error: this cat 1 does not exist");
-            ensure!(<Kitties<T>>::exists(kitty_id_2), "Error: this cat 2 does not exist");
+            ensure!(sender != to, "Error: you can not transfer your kitties to yourself");
+            ensure!(T::IsSelfControlled::is_self_controlled(&sender, &to), "Error: the destination account is not controlled by you");
+            Self::_ensure_not_adoption_center(&to)?;
 
-            // 子供に振られるidを計算する。
-            let nonce = <Nonce<T>>::get();
-            let random_hash = (<system::Module<T>>::random_seed(), &sender, nonce)
-                                .using_encoded(<T as system::Trait>::Hashing::hash);
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership for this kitty");
 
-            // 親を引き出す。
-            let kitty_1 = Self::kitty(kitty_id_1);
-            let kitty_2 = Self::kitty(kitty_id_2);
+            Self::_transfer_from(sender, to, kitty_id)
+        }
 
-            // 最終的な子供のDNA（初期値として片親のDNAをコピー）
-            let mut final_dna = kitty_1.dna;
+        // 自分のkittyに共同所有者`co_owner`を加える関数。共同所有者は一匹につき一人までである。
+        // `co_owner`の同意はいらない。加えた時点で、共同所有者はそのkittyで繁殖でき、
+        // 転送や売却を承認しないことで止められる（拒否権を持つ）。外せるのは共同所有者自身だけである。
+        fn add_co_owner(origin, kitty_id: KittyId<T::Hash>, co_owner: T::AccountId) -> Result {
+            let kitty_id = *kitty_id;
 
-            // DNAをシェイキング！
-            for (i, (dna_2_element, r)) in kitty_2.dna.as_ref().iter().zip(random_hash.as_ref().iter()).enumerate() {
-                if r % 2 == 0 {
-                    final_dna.as_mut()[i] = *dna_2_element;
-                }
-            }
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-            // 子供誕生
-            let new_kitty = Kitty {
-                id: random_hash,
-                dna: final_dna,
-                price: <T::Balance as As<u64>>::sa(0),
-                gen: cmp::max(kitty_1.gen, kitty_2.gen) + 1,
-            };
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership for this kitty");
+            ensure!(co_owner != sender, "Error: you can not be the co-owner of your own kitty");
+            ensure!(!<CoOwner<T>>::exists(kitty_id), "Error: this kitty already has a co-owner");
 
-            // 子供の所有権を記録する。
-            Self::_mint(sender, random_hash, new_kitty)?;
+            <CoOwner<T>>::insert(kitty_id, &co_owner);
 
-            // nonce更新
-            <Nonce<T>>::mutate(|n| *n += 1);
+            Self::deposit_event(RawEvent::CoOwnerAdded(kitty_id, co_owner));
 
             Ok(())
-
         }
-    }
-}
 
-impl <T: Trait> Module<T> {
+        // 共同所有者が自分からkittyの共同所有をやめる関数。
+        // 所有者だけで外せてしまうと承認の意味がなくなるので、共同所有者しか呼べない。
+        fn remove_co_owner(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
 
-    // 新たなkittyを記録するヘルパー関数を用意。
-    fn _mint(to: T::AccountId, kitty_id: T::Hash, new_kitty: Kitty<T::Hash, T::Balance>) -> Result {
-        // 計算したrandom_hashが衝突していないことを確認する。
-        ensure!(!<KittyOwner<T>>::exists(kitty_id), "Error: the kitty coressponding to this ID already exit!");
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-        // Verify first, write lastの原則：この人が現在何匹のkittyを所有しているかを取得する。
-        let owned_kitty_count = Self::owned_kitty_count(&to);
+            ensure!(Self::co_owner_of(kitty_id).as_ref() == Some(&sender), "Error: you are not the co-owner of this kitty");
 
-        // Verify first, write lastの原則：新しいkittyを所有するので更新する。
-        let new_owned_kitty_count = owned_kitty_count.checked_add(1)
-            .ok_or("Error: Overflow happed when trying to register a new kitty in your account balance")?;
+            <CoOwner<T>>::remove(kitty_id);
+            <CoOwnerApproval<T>>::remove(kitty_id);
 
-        // Verify first, write lastの原則：現在登録されているkittiesの個体数を確認する。
-        let all_kitties_count = Self::all_kitties_count();
+            Self::deposit_event(RawEvent::CoOwnerRemoved(kitty_id, sender));
 
-        // Verify first, write lastの原則：これから登録しようとしているkittyを追加してoverflowしないかを確認する。
-        let new_all_kitties_count = all_kitties_count.checked_add(1)
-            .ok_or("Error: Overflow happened when trying to register a new kitty")?;
+            Ok(())
+        }
 
-        // (random_hash, new_kitty)を登録する。
-        <Kitties<T>>::insert(kitty_id, new_kitty);
+        // 共同所有者がkittyの次の転送か売却を承認する（`approved`がfalseならば取り消す）関数。
+        fn set_co_owner_approval(origin, kitty_id: KittyId<T::Hash>, approved: bool) -> Result {
+            let kitty_id = *kitty_id;
 
-        // (生成者を一意に区別するハッシュ値, 生成者)を登録する。
-        <KittyOwner<T>>::insert(kitty_id, &to);
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-        // (all_kitties_count, random_hash)を登録する。all_kitties_countは0オリジンの通し番号となる。
-        <AllKittiesArray<T>>::insert(all_kitties_count, kitty_id);
+            ensure!(Self::co_owner_of(kitty_id).as_ref() == Some(&sender), "Error: you are not the co-owner of this kitty");
 
-        // 「現在のkittiesの個体数」を更新する。
-        <AllKittiesCount<T>>::put(new_all_kitties_count);
+            if approved {
+                <CoOwnerApproval<T>>::insert(kitty_id, true);
+            } else {
+                <CoOwnerApproval<T>>::remove(kitty_id);
+            }
 
-        // (random_hash, all_kitties_count)を登録する。
-        <AllKittiesIndex<T>>::insert(kitty_id, all_kitties_count);
+            Self::deposit_event(RawEvent::CoOwnerApproval(sender, kitty_id, approved));
 
-        // // (生成者, 生成者を一意に区別するハッシュ値)を登録する。
-        // <OwnedKitty<T>>::insert(&sender, random_hash);
+            Ok(())
+        }
 
-        // ((ユーザー, その人にとって何匹目か), kittyの識別子)を記録する。
-        // こうすることで、二次元リストをエミュレートする。「誰の」「何番目か」で一匹を指定できる。
-        <OwnedKittiesArray<T>>::insert((to.clone(), owned_kitty_count), kitty_id);
+        // `operator`に、呼び出し側の全てのkittyの転送とpriceの設定を任せる（`approved`がfalseならば取り消す）関数。
+        // アカウント単位の設定なので、kittyが転送されても消えない。
+        fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) -> Result {
 
-        // (ユーザー, ユーザーの所有しているkittyの個体数)を登録する。
-        <OwnedKittiesCount<T>>::insert(&to, new_owned_kitty_count);
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+            ensure!(sender != operator, "Error: you can not make yourself your own operator");
 
-        // 今生成されたkittyが、その所有者にとって何番目のkittyなのかを登録する。
-        <OwnedKittiesIndex<T>>::insert(kitty_id, owned_kitty_count);
+            if approved {
+                <OperatorApprovals<T>>::insert((sender.clone(), operator.clone()), true);
+            } else {
+                <OperatorApprovals<T>>::remove((sender.clone(), operator.clone()));
+            }
 
-        // トランザクション執行後のイベントを吐く。
-        Self::deposit_event(RawEvent::Created(to, kitty_id));
+            Self::deposit_event(RawEvent::ApprovalForAll(sender, operator, approved));
 
-        Ok(())
-    }
+            Ok(())
+        }
 
-    // 転送元と転送先、転送されるkittyを特定するハッシュ値を引数に、転送を実行しその成否を返すヘルパー関数
-    fn _transfer_from(from: T::AccountId, to: T::AccountId, kitty_id: T::Hash) -> Result {
+        // 売りに出しているkittyに、希望する通貨の表示用ヒントを添える関数。空ならばヒントを外す。
+        fn set_currency_hint(origin, kitty_id: KittyId<T::Hash>, hint: Vec<u8>) -> Result {
+            let kitty_id = *kitty_id;
 
-        // Verify first, write lastの原則：呼び出し元が転送したいkittyの所有者であるかを確認する。
-        let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
-        ensure!(owner == from, "Error: `from` account have no ownership for this kitty");
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-        // 所有者の中の何番目のkittyを転送したいのかを確認する。
-        let owned_kitty_count_from = Self::owned_kitty_count(&from);
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(Self::_is_owner_or_operator(&owner, &sender), "Error: you have no ownership to this kitty");
 
-        // 転送先では何番目のkittyとして扱われるのかを確認する。
-        let owned_kitty_count_to = Self::owned_kitty_count(&to);
+            ensure!(Self::is_listed(&Self::kitty(kitty_id)), "Error: this kitty is not for sale");
+            ensure!(hint.len() <= MAX_CURRENCY_HINT_LENGTH, "Error: the currency hint is too long");
 
-        // 転送先がすでにn匹のkittyを所有しているならば、転送先ではn+1匹目として扱われることを確認する。
-        let new_owned_kitty_count_to = owned_kitty_count_to.checked_add(1)
-            .ok_or("Error: happend overflow of `to`'s kitty balance while executing transfer method")?;
+            if hint.is_empty() {
+                <ListingCurrencyHint<T>>::remove(kitty_id);
+            } else {
+                <ListingCurrencyHint<T>>::insert(kitty_id, hint);
+            }
 
-        // 転送元がn匹のkittyを所有しているならば、転送してしまうと所有している個体数が1減ることを確認する。
-        let new_owned_kitty_count_from = owned_kitty_count_from.checked_sub(1)
-            .ok_or("Error: happend underflow of `from`'s kitty balance while executing transfer method")?;
+            Ok(())
+        }
 
-        // 転送されるkittyが転送前の所有者にとって何番目の個体なのかを確認する。
-        let kitty_index = <OwnedKittiesIndex<T>>::get(kitty_id);
+        // 呼び出し側が所有しているkittyを、所有リストの末尾から最大`limit`匹まとめて`to`に転送する関数。アカウントの移行に使う。
+        // `limit`がMaxBatchSizeを超えると失敗するので、全てのkittyを移すには所有数が0になるまで呼び直す。
+        fn transfer_all(origin, to: T::AccountId, limit: u64) -> Result {
 
-        // Swap and Popメソッドで転送を実施する。
-        // 転送したいkittyが転送前所有者の最直近に得た個体だったらPopすればいい。
-        // そうでないならば、転送されるkittyの位置に、最直近で得た個体へのポインタを貼る（Swap）。
-        if kitty_index != new_owned_kitty_count_from {
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+            ensure!(sender != to, "Error: you can not transfer your kitties to yourself");
+            Self::_ensure_not_adoption_center(&to)?;
+
+            // Verify first, write lastの原則：一度に転送するkittyの数がMaxBatchSize以下であることを確認する。
+            Self::_ensure_batch_size(limit)?;
+            let owned_kitty_count = Self::owned_kitty_count(&sender);
+            let batch_size = cmp::min(limit, owned_kitty_count);
+
+            // Verify first, write lastの原則：転送先の個体数がoverflowしないことを確認する。
+            Self::owned_kitty_count(&to).checked_add(batch_size)
+                .ok_or("Error: happend overflow of `to`'s kitty balance while executing transfer method")?;
+
+            // Verify first, write lastの原則：途中で失敗しないように、soulboundなkittyが含まれていないことを先に確認する。
+            let kitty_ids: Vec<T::Hash> = (owned_kitty_count - batch_size..owned_kitty_count)
+                .map(|i| Self::kitty_of_owner_by_index((sender.clone(), i)))
+                .collect();
+            ensure!(kitty_ids.iter().all(|kitty_id| !Self::kitty(kitty_id).soulbound),
+                "Error: some of your kitties are soulbound and can not be transferred");
+            ensure!(kitty_ids.iter().all(|kitty_id| Self::_co_owner_consents(*kitty_id)),
+                "Error: CoOwnerApprovalRequired: some of your kitties need their co-owner's approval to be transferred");
+
+            // Verify first, write lastの原則：一匹ずつの転送と同じく、kittyごとに手数料がかかるので、合計を払えることを確認する。
+            let transfer_fee = Self::transfer_fee().checked_mul(&<T::Balance as As<u64>>::sa(batch_size))
+                .ok_or("Error: Overflow happened when calculating the total transfer fee")?;
+            ensure!(<balances::Module<T>>::free_balance(&sender) >= transfer_fee,
+                "Error: you don't have enough free balance to pay the transfer fee");
+
+            // 手数料の合計をFeeCollectorに支払う。支払いが失敗しても、まだ何も書き込んでいない。
+            if !transfer_fee.is_zero() {
+                <balances::Module<T>>::make_transfer(&sender, &Self::fee_collector(), transfer_fee)?;
+            }
 
-            // 転送元が最直近に所有権を得たkittyのインデックスを確認する。
-            let last_kitty_id = <OwnedKittiesArray<T>>::get((from.clone(), new_owned_kitty_count_from));
+            // 最後のkittyから順に転送すれば、Swap and PopのPopだけで転送元のリストが縮んでいく。
+            for kitty_id in kitty_ids.into_iter().rev() {
+                Self::_transfer_from(sender.clone(), to.clone(), kitty_id)?;
+            }
 
-            <OwnedKittiesArray<T>>::insert((from.clone(), kitty_index), last_kitty_id);
-            <OwnedKittiesIndex<T>>::insert(last_kitty_id, kitty_index);
+            Ok(())
         }
 
-        // 転送されたkittyの所有者を更新する。
-        <KittyOwner<T>>::insert(&kitty_id, &to);
+        // 呼び出し側が所有しているkittyにご飯をあげて、happinessを最大に戻す関数。
+        fn feed_kitty(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
 
-        // 転送されたkittyは所有者にとって何番目であるかが変更されたので更新する。
-        <OwnedKittiesIndex<T>>::insert(kitty_id, owned_kitty_count_to);
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
 
-        // 転送した側は転送して個体数が減ったので更新する。\
-        <OwnedKittiesArray<T>>::remove((from.clone(), new_owned_kitty_count_from));
+            // Verify first, write lastの原則：ご飯をあげたいkittyの所有者であることを確認する。
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership for this kitty");
 
-        // 転送された側は持っている個体数が増えたので更新する。
-        // `to`の`owned_kitty_count_to`匹目が`kitty_id`という意味である。
-        <OwnedKittiesArray<T>>::insert((to.clone(), owned_kitty_count_to), kitty_id);
+            // Verify first, write lastの原則：手数料を払えることを確認する。
+            let feeding_fee = Self::feeding_fee();
+            ensure!(<balances::Module<T>>::free_balance(&sender) >= feeding_fee,
+                "Error: you don't have enough free balance to pay the feeding fee");
 
-        //  双方の持っている個体数を更新する。
-        <OwnedKittiesCount<T>>::insert(&from, new_owned_kitty_count_from);
-        <OwnedKittiesCount<T>>::insert(&to, new_owned_kitty_count_to);
+            if !feeding_fee.is_zero() {
+                <balances::Module<T>>::make_transfer(&sender, &Self::fee_collector(), feeding_fee)?;
+            }
 
-        // Transferredイベントを吐く。
-        Self::deposit_event(RawEvent::Transferred(from, to, kitty_id));
+            let mut kitty = Self::kitty(kitty_id);
+            kitty.last_fed_at = <system::Module<T>>::block_number();
+            Self::_touch(&mut kitty);
+            <Kitties<T>>::insert(kitty_id, kitty);
 
-        Ok(())
-    }
-}
\ No newline at end of file
+            Self::deposit_event(RawEvent::Fed(sender, kitty_id));
+
+            Ok(())
+        }
+
+        // 呼び出し側が所有しているkittyを焼却する関数。soulboundなkittyも焼却できる。
+        fn burn_kitty(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：焼却したいkittyの所有者であることを確認する。
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership for this kitty");
+            ensure!(!<CoOwner<T>>::exists(kitty_id), "Error: this kitty has a co-owner and can not be burned");
+
+            Self::_burn(sender, kitty_id)
+        }
+
+        // 呼び出し側が買いたいkittyのIDと買取額を引数に与えて、購入を実行し、その成否を返す関数を定義する。
+        fn buy_kitty(origin, kitty_id: KittyId<T::Hash>, max_price: T::Balance) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：買いたいkittyが存在することを確認する。
+            ensure!(<Kitties<T>>::exists(kitty_id), "Error: invalid kitty id: this kitty does not exist");
+
+            // Verify first, write lastの原則：kittyの所有者が正当であることを確認する。
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner != sender, "Error: you can not buy your own kitty");
+
+            // 売買されるkittyを引き出す。
+            let kitty = Self::kitty(kitty_id);
+            // 売却額を確認する。
+            let kitty_price = kitty.price;
+
+            // soulboundなkittyは売買できない。代金を払う前に確認する。
+            ensure!(!kitty.soulbound, "Error: this kitty is soulbound and can not be sold");
+
+            // 出品されていないkittyは売却対象ではない。
+            ensure!(Self::is_listed(&kitty), "Error: this kitty you want to buy is not for sale");
+
+            // 買取側の口座残高が売却額以下でないと買えないので確認する。
+            ensure!(kitty_price <= max_price, "Error: this kitty you want to buy costs more than your max price");
+
+            // Verify first, write lastの原則：代金を動かす前に、kittyを転送できることを確認しておく。
+            // runtimeの関数呼び出しは再入されないので、心配なのは途中での失敗だけである。
+            // 失敗しうる確認を全て済ませてから代金を払い、最後に転送することで、代金を払ったのにkittyが届かないことをなくす。
+            Self::_ensure_can_transfer(&owner, &sender, kitty_id)?;
+
+            // 売却額を売却側、minter、焼却に分配する。settle_saleは支払いの確認を全て済ませてから代金を動かすので、
+            // 失敗するならば何も払われていない。
+            Self::settle_sale(&sender, kitty_price, &owner, Self::minter_of(kitty_id))?;
+
+            // kittyを売却側から購入側へ転送する。出品は転送の中で取り下げられる。
+            Self::_transfer_from(owner.clone(), sender.clone(), kitty_id)?;
+
+            // 売却履歴に記録する。
+            Self::_record_sale(kitty_id, kitty_price);
+            Self::_record_event(EventSummary::Sold(kitty_id, kitty_price));
+
+            // Boughtイベントを吐く。
+            Self::deposit_event(RawEvent::Bought(sender, owner, kitty_id, kitty_price));
+
+            Ok(())
+        }
+
+        // 親となる二匹を引数として与えて、子供を作らせ、その成否を返す関数。
+        fn breed_kitty(origin, kitty_id_1: KittyId<T::Hash>, kitty_id_2: KittyId<T::Hash>) -> Result {
+            let (kitty_id_1, kitty_id_2) = (*kitty_id_1, *kitty_id_2);
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            Self::_breed(sender.clone(), sender.clone(), sender, kitty_id_1, kitty_id_2, Zero::zero())?;
+
+            Ok(())
+        }
+
+        // 自分の二匹を繁殖させ、生まれた子供を`recipient`に贈る関数。手数料や繁殖回数は呼び出し側のものとして数える。
+        fn breed_and_gift(origin, kitty_id_1: KittyId<T::Hash>, kitty_id_2: KittyId<T::Hash>, recipient: T::AccountId) -> Result {
+            let (kitty_id_1, kitty_id_2) = (*kitty_id_1, *kitty_id_2);
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+            ensure!(recipient != sender, "Error: use breed_kitty to breed a kitty for yourself");
+
+            // 両親とも自分のkittyであることは繁殖の中で確認される。
+            Self::_breed(sender.clone(), sender, recipient, kitty_id_1, kitty_id_2, Zero::zero())
+        }
+
+        // 自分のkittyの組を`pairs`でまとめて繁殖させる関数。
+        // 一組でも繁殖できなければ、どの組も繁殖させない。そのために、全ての組と手数料の合計を確かめてから繁殖を始める。
+        fn breed_many(origin, pairs: Vec<(KittyId<T::Hash>, KittyId<T::Hash>)>) -> Result {
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            ensure!(!pairs.is_empty(), "Error: there are no pairs to breed");
+            Self::_ensure_batch_size(pairs.len() as u64)?;
+            let pairs: Vec<(T::Hash, T::Hash)> = pairs.into_iter().map(|(kitty_id_1, kitty_id_2)| (*kitty_id_1, *kitty_id_2)).collect();
+
+            // Verify first, write lastの原則：同じkittyが二つの組に入っていないことを確認する。
+            // 一度繁殖した親はクールダウンに入るので、二組目の繁殖で失敗してしまう。
+            let mut parents: Vec<T::Hash> = Vec::new();
+            for &(kitty_id_1, kitty_id_2) in &pairs {
+                for kitty_id in &[kitty_id_1, kitty_id_2] {
+                    ensure!(!parents.contains(kitty_id), "Error: a cat can only appear in one pair");
+                    parents.push(*kitty_id);
+                }
+            }
+
+            // Verify first, write lastの原則：全ての組が自分のkittyで繁殖できることと、手数料の合計を払えることを確認する。
+            let now = <system::Module<T>>::block_number();
+            let mut total_fee = <T::Balance as As<u64>>::sa(0);
+            for &(kitty_id_1, kitty_id_2) in &pairs {
+                let (_, _, breeding_fee) = Self::_check_parents(&sender, &sender, kitty_id_1, kitty_id_2, now)?;
+                total_fee = total_fee.checked_add(&breeding_fee)
+                    .ok_or("Error: Overflow happened when calculating the total breeding fee")?;
+            }
+            let free_balance = <balances::Module<T>>::free_balance(&sender);
+            ensure!(free_balance >= total_fee,
+                "Error: InsufficientBalance: you don't have enough free balance to pay the breeding fees");
+            // 手数料は組ごとにslashするが、slashで残高がexistential depositを下回るとアカウントごと消えてしまい、
+            // 残りの組の手数料が払えなくなる。全ての手数料を払った後もアカウントが残ることを確認する。
+            ensure!(total_fee.is_zero() || free_balance - total_fee >= <balances::Module<T>>::existential_deposit(),
+                "Error: InsufficientBalance: paying the breeding fees would leave your balance below the existential deposit");
+
+            // Verify first, write lastの原則：全ての組を繁殖させても、繁殖回数や生まれる数の上限を超えないことを確認する。
+            // 組の数はMaxBatchSize以下なので、u32やu64に収まる。
+            let breed_count = pairs.len() as u32;
+            let litter_count = breed_count as u64 * cmp::max(Self::litter_size(), 1) as u64;
+            let (_, breeds) = Self::_current_breed_period(&sender, now)?;
+            let max_breeds = Self::max_breeds_per_period();
+            let new_breeds = breeds.checked_add(breed_count)
+                .ok_or("Error: Overflow happened when counting your breeds in this period")?;
+            ensure!(max_breeds == 0 || new_breeds <= max_breeds, "Error: BreedQuotaExceeded: you have bred too many times in this period");
+            Self::_ensure_breed_ratio(litter_count)?;
+            Self::_ensure_mint_capacity(litter_count)?;
+            Self::owned_kitty_count(&sender).checked_add(litter_count)
+                .ok_or("Error: Overflow happed when trying to register the litters in your account balance")?;
+
+            for (kitty_id_1, kitty_id_2) in pairs {
+                Self::_breed(sender.clone(), sender.clone(), sender.clone(), kitty_id_1, kitty_id_2, Zero::zero())?;
+            }
+
+            Ok(())
+        }
+
+        // 他のアカウントが所有するkittyとの繁殖を、手数料`offer_fee`を提示して申し込む関数。手数料は承認されるまで予約（reserve）しておく。
+        fn request_breed(origin, my_kitty: KittyId<T::Hash>, their_kitty: KittyId<T::Hash>, offer_fee: T::Balance) -> Result {
+            let (my_kitty, their_kitty) = (*my_kitty, *their_kitty);
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：自分のkittyと、他人のkittyの組み合わせであることを確認する。
+            let my_owner = Self::owner_of(my_kitty).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(my_owner == sender, "Error: you have no ownership to this kitty");
+            let their_owner = Self::owner_of(their_kitty).ok_or("Error: there is no owner for the partner kitty")?;
+            ensure!(their_owner != sender, "Error: use breed_kitty to breed your own kitties");
+
+            ensure!(!<BreedRequests<T>>::exists((my_kitty, their_kitty)), "Error: you have already requested this breed");
+
+            <balances::Module<T>>::reserve(&sender, offer_fee)?;
+            <BreedRequests<T>>::insert((my_kitty, their_kitty), (sender.clone(), offer_fee));
+
+            Self::deposit_event(RawEvent::BreedRequested(sender, my_kitty, their_kitty, offer_fee));
+
+            Ok(())
+        }
+
+        // 繁殖の申し込みを取り下げ、予約していた手数料を戻す関数。
+        fn cancel_breed_request(origin, my_kitty: KittyId<T::Hash>, their_kitty: KittyId<T::Hash>) -> Result {
+            let (my_kitty, their_kitty) = (*my_kitty, *their_kitty);
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let (requester, offer_fee) = Self::breed_request((my_kitty, their_kitty)).ok_or("Error: there is no such breed request")?;
+            ensure!(requester == sender, "Error: this breed request is not yours");
+
+            <BreedRequests<T>>::remove((my_kitty, their_kitty));
+            <balances::Module<T>>::unreserve(&sender, offer_fee);
+
+            Ok(())
+        }
+
+        // 自分のkitty`my_kitty`に届いた、`their_kitty`との繁殖の申し込みを承認する関数。
+        // 子供は申し込んだ側のものになり、提示された手数料は承認した側に支払われる。
+        fn approve_breed(origin, my_kitty: KittyId<T::Hash>, their_kitty: KittyId<T::Hash>) -> Result {
+            let (my_kitty, their_kitty) = (*my_kitty, *their_kitty);
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let (requester, offer_fee) = Self::breed_request((their_kitty, my_kitty)).ok_or("Error: there is no such breed request")?;
+
+            // Verify first, write lastの原則：申し込み後に所有者が変わっていないことを確認する。
+            let my_owner = Self::owner_of(my_kitty).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(my_owner == sender, "Error: you have no ownership to this kitty");
+            let their_owner = Self::owner_of(their_kitty).ok_or("Error: there is no owner for the partner kitty")?;
+            ensure!(their_owner == requester, "Error: the requester no longer owns the partner kitty");
+
+            // 両親の所有権やクールダウンなどは繁殖の中でも確認される。二匹目の親は承認した側のkittyとして確認される。
+            // 予約していた手数料は、繁殖の確認が全て済んでから、何かを書き込む前に承認した側へ移される。
+            Self::_breed(requester.clone(), sender.clone(), requester.clone(), their_kitty, my_kitty, offer_fee)?;
+
+            <BreedRequests<T>>::remove((their_kitty, my_kitty));
+
+            Self::deposit_event(RawEvent::BreedApproved(sender, my_kitty, their_kitty));
+
+            Ok(())
+        }
+
+        // 初めて呼び出したアカウントにgen 0のkittyを何匹か配る関数。一つのアカウントにつき一度しか受け取れない。
+        fn claim_starter_pack(origin) -> Result {
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：まだ受け取っていないことを確認する。
+            ensure!(!Self::claimed_starter(&sender), "Error: StarterAlreadyClaimed: you have already claimed your starter pack");
+
+            let pack_size = Self::starter_pack_size() as u64;
+            ensure!(pack_size > 0, "Error: starter packs are not available on this chain");
+
+            // Verify first, write lastの原則：途中でoverflowしないことを、書き込む前に確認しておく。
+            Self::owned_kitty_count(&sender).checked_add(pack_size)
+                .ok_or("Error: Overflow happed when trying to register starter kitties in your account balance")?;
+            Self::all_kitties_count().checked_add(pack_size)
+                .ok_or("Error: Overflow happened when trying to register starter kitties")?;
+            Self::_ensure_mint_capacity(pack_size)?;
+
+            for _ in 0..pack_size {
+                Self::_create_kitty(sender.clone(), false)?;
+            }
+
+            <ClaimedStarter<T>>::insert(&sender, true);
+
+            Ok(())
+        }
+
+        // 所有者のアカウントがbalancesから削除された（reapされた）kittyを引き取る関数。
+        // 最後の動きからAbandonmentPeriodのブロック数が経っていなければならない。
+        // 残高が0というだけでは、一度も入金されていないアドレスと区別できない。そこで所有者が一度でも
+        // 署名したトランザクションを送っている（nonceが0でない）ことも求める。それでも、受け取った後に
+        // 一度も署名せず残高もないアドレスのkittyは引き取れないし、逆に鍵を持っている所有者でも
+        // 残高を失って放置すれば引き取られうる。AbandonmentPeriodはこの危険を踏まえて十分長くすること。
+        fn claim_abandoned(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let abandonment_period = Self::abandonment_period();
+            ensure!(!abandonment_period.is_zero(), "Error: abandoned kitties can not be claimed on this chain");
+
+            // Verify first, write lastの原則：所有者のアカウントがもう存在しないことを確認する。
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner != sender, "Error: you already own this kitty");
+            // AdoptionCenterには残高がないが、預かっているkittyはadoptで順番に引き取ってもらう。
+            ensure!(owner != Self::adoption_center(), "Error: this kitty is up for adoption; use adopt to claim it");
+            ensure!(<balances::Module<T>>::total_balance(&owner).is_zero(), "Error: the owner of this kitty still exists");
+            ensure!(!<system::Module<T>>::account_nonce(&owner).is_zero(), "Error: the owner of this kitty has never been active");
+
+            // Verify first, write lastの原則：放置されてから十分な時間が経っていることを確認する。
+            let claimable_at = Self::kitty(kitty_id).last_active_at.checked_add(&abandonment_period)
+                .ok_or("Error: Overflow happened when calculating the abandonment period")?;
+            ensure!(<system::Module<T>>::block_number() >= claimable_at, "Error: this kitty has not been abandoned long enough");
+
+            Self::_transfer_from(owner, sender.clone(), kitty_id)?;
+
+            Self::deposit_event(RawEvent::Claimed(sender, kitty_id));
+
+            Ok(())
+        }
+
+        // 自分のkittyを里親募集に出す関数。kittyはAdoptionCenterに預けられ、誰でもadoptで無料で引き取れる。
+        fn put_up_for_adoption(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership for this kitty");
+
+            // Verify first, write lastの原則：キューの末尾の通し番号と数がoverflowしないことを確認する。
+            let head = Self::adoption_pool_head();
+            let count = Self::adoption_pool_count();
+            let tail = head.checked_add(count)
+                .ok_or("Error: Overflow happened when adding the kitty to the adoption pool")?;
+            let new_count = count.checked_add(1)
+                .ok_or("Error: Overflow happened when adding the kitty to the adoption pool")?;
+
+            // 転送できるかどうか（soulboundや共同所有者の承認）は転送の中で確認される。
+            Self::_transfer_from(sender.clone(), Self::adoption_center(), kitty_id)?;
+
+            <AdoptionPool<T>>::insert(tail, kitty_id);
+            <AdoptionPoolCount<T>>::put(new_count);
+
+            Self::deposit_event(RawEvent::PutUpForAdoption(sender, kitty_id));
+
+            Ok(())
+        }
+
+        // 里親募集中のkittyのうち、最も早く預けられたkittyを引き取る関数。
+        fn adopt(origin) -> Result {
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let adoption_center = Self::adoption_center();
+            ensure!(sender != adoption_center, "Error: the adoption center can not adopt its own kitties");
+
+            let count = Self::adoption_pool_count();
+            ensure!(count > 0, "Error: NothingToAdopt: there are no kitties up for adoption");
+
+            let head = Self::adoption_pool_head();
+            let new_head = head.checked_add(1)
+                .ok_or("Error: Overflow happened when taking the kitty from the adoption pool")?;
+            let kitty_id = Self::adoption_pool_at(head);
+
+            Self::_transfer_from(adoption_center, sender.clone(), kitty_id)?;
+
+            <AdoptionPool<T>>::remove(head);
+            <AdoptionPoolHead<T>>::put(new_head);
+            // countが1以上であることは確認済みなので、素の-で構わない。
+            <AdoptionPoolCount<T>>::put(count - 1);
+
+            Self::deposit_event(RawEvent::Adopted(sender, kitty_id));
+
+            Ok(())
+        }
+
+        // 管理者を`new_admin`に替える関数。Noneならば管理者をなくす。rootか今の管理者だけが呼べる。
+        // 管理者はsudoの権限なしに、このモジュールの運用のための関数を呼べる。
+        fn set_admin(origin, new_admin: Option<T::AccountId>) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            match new_admin {
+                Some(ref new_admin) => <Admin<T>>::put(new_admin),
+                None => <Admin<T>>::kill(),
+            }
+
+            Self::deposit_event(RawEvent::AdminChanged(new_admin));
+
+            Ok(())
+        }
+
+        // `recipe_id`のレシピを定義する関数。rootか管理者だけが呼べる。既にあれば置き換える。
+        // 珍しさの段階が`input_tier`のkittyを`input_count`匹焼却すると、`output_traits`の特徴を全て持つkittyが一匹作られる。
+        fn set_recipe(origin, recipe_id: u32, input_count: u32, input_tier: u32, output_traits: u64) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            // 焼却せずに作れてしまうと、kittyをいくらでも増やせる。
+            ensure!(input_count > 0, "Error: a recipe must burn at least one kitty");
+            ensure!(input_count <= MAX_RECIPE_INPUTS, "Error: a recipe can not burn that many kitties");
+            // 特徴はdnaの各バイトに一つずつなので、dnaのバイト数を超えるビットの特徴は作れない。
+            let dna_length = T::Hash::default().as_ref().len();
+            ensure!(dna_length >= 64 || output_traits >> dna_length == 0, "Error: a recipe can not give traits beyond the length of the dna");
+
+            <Recipes<T>>::insert(recipe_id, Recipe { input_count, input_tier, output_traits });
+
+            Self::deposit_event(RawEvent::RecipeSet(recipe_id));
+
+            Ok(())
+        }
+
+        // `recipe_id`のレシピを取り除く関数。rootか管理者だけが呼べる。
+        fn remove_recipe(origin, recipe_id: u32) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            ensure!(<Recipes<T>>::exists(recipe_id), "Error: this recipe does not exist");
+
+            <Recipes<T>>::remove(recipe_id);
+
+            Self::deposit_event(RawEvent::RecipeRemoved(recipe_id));
+
+            Ok(())
+        }
+
+        // 自分のkitty`inputs`を`recipe_id`のレシピに従って焼却し、新しいkittyを一匹作る関数。
+        fn craft(origin, recipe_id: u32, inputs: Vec<KittyId<T::Hash>>) -> Result {
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let recipe = Self::recipe(recipe_id).ok_or("Error: this recipe does not exist")?;
+            ensure!(inputs.len() == recipe.input_count as usize, "Error: the number of kitties does not match the recipe");
+
+            // Verify first, write lastの原則：焼却する前に、全てのkittyがレシピに合うことを確認する。
+            let mut max_gen = 0;
+            for (i, kitty_id) in inputs.iter().enumerate() {
+                let kitty_id = **kitty_id;
+                ensure!(!inputs[..i].iter().any(|id| **id == kitty_id), "Error: the same kitty can not be burned twice");
+
+                let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+                ensure!(owner == sender, "Error: you have no ownership for this kitty");
+                ensure!(!<CoOwner<T>>::exists(kitty_id), "Error: this kitty has a co-owner and can not be burned");
+
+                let kitty = Self::kitty(kitty_id);
+                // soulboundなkittyを材料にして、売買も転送もできるkittyに作り替えることはできない。
+                ensure!(!kitty.soulbound, "Error: this kitty is soulbound and can not be used for crafting");
+                ensure!(Self::rarity_tier(&kitty) == recipe.input_tier, "Error: this kitty does not have the rarity tier the recipe requires");
+                max_gen = cmp::max(max_gen, kitty.gen);
+            }
+
+            let gen = max_gen.checked_add(1)
+                .ok_or("Error: Overflow happened when calculating the crafted kitty's generation")?;
+
+            // 作るkittyのidは_create_kittyと同じように計算し、dnaにはレシピの特徴を書き込む。
+            let nonce = <Nonce<T>>::get();
+            let new_nonce = nonce.checked_add(1)
+                .ok_or("Error: Overflow happened when incrementing the nonce")?;
+            let kitty_id = (T::Randomness::random_seed(), &sender, nonce)
+                .using_encoded(<T as system::Trait>::Hashing::hash);
+            let kitty_id = Self::_unretired_id(kitty_id);
+            Self::_ensure_can_mint(&sender, kitty_id)?;
+            // 焼却する前の個体数で確認するので、総数がMaxTotalSupplyちょうどのときは作れない。
+            let new_mints = Self::_ensure_mint_capacity(1)?;
+
+            let mut dna = kitty_id;
+            for (i, byte) in dna.as_mut().iter_mut().take(64).enumerate() {
+                if recipe.output_traits & (1 << i) != 0 {
+                    *byte |= 0x80;
+                }
+            }
+
+            for kitty_id in inputs {
+                Self::_burn(sender.clone(), *kitty_id)?;
+            }
+
+            let new_kitty = Kitty {
+                id: kitty_id,
+                dna,
+                price: <T::Balance as As<u64>>::sa(0),
+                gen,
+                cooldown_end: <T::BlockNumber as As<u64>>::sa(0),
+                soulbound: false,
+                trait_flags: 0,
+                minted_at: <T::BlockNumber as As<u64>>::sa(0),
+                parents: None,
+                last_fed_at: <T::BlockNumber as As<u64>>::sa(0),
+                last_active_at: <T::BlockNumber as As<u64>>::sa(0),
+            };
+            Self::_mint(sender.clone(), kitty_id, new_kitty, false)?;
+            <MintsThisBlock<T>>::put(new_mints);
+            <Nonce<T>>::put(new_nonce);
+
+            Self::deposit_event(RawEvent::Crafted(sender, recipe_id, kitty_id));
+
+            Ok(())
+        }
+
+        // `until`のブロックになるまで繁殖を止める関数。rootか管理者だけが呼べる。今のブロック以前を渡すと、すぐに再開する。
+        fn pause_breeding_until(origin, until: T::BlockNumber) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            if until > <system::Module<T>>::block_number() {
+                <BreedingPausedUntil<T>>::put(until);
+            } else {
+                <BreedingPausedUntil<T>>::kill();
+            }
+
+            Self::deposit_event(RawEvent::BreedingPausedUntil(until));
+
+            Ok(())
+        }
+
+        // 珍しいdnaに表示名をつける関数。rootか管理者だけが呼べる。空の名前を渡すと表示名を外す。
+        fn set_dna_name(origin, dna: T::Hash, name: Vec<u8>) -> Result {
+
+            // Verify first, write lastの原則：rootか管理者からの呼び出しであることを確認する。
+            Self::_ensure_root_or_admin(origin)?;
+
+            ensure!(name.len() <= MAX_DNA_NAME_LENGTH, "Error: the dna name is too long");
+
+            // 表示名が変わらないならば、何も書き込まずに終わる。
+            if Self::dna_name(dna) == name {
+                return Ok(());
+            }
+
+            if name.is_empty() {
+                <DnaNames<T>>::remove(dna);
+            } else {
+                <DnaNames<T>>::insert(dna, name);
+            }
+
+            Self::deposit_event(RawEvent::DnaNameSet(dna));
+
+            Ok(())
+        }
+
+        // 名前付きのコレクションを作る関数。
+        fn create_collection(origin, name: Vec<u8>) -> Result {
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            ensure!(name.len() <= MAX_COLLECTION_NAME_LENGTH, "Error: the collection name is too long");
+
+            // Verify first, write lastの原則：次のコレクションIDがoverflowしないことを確認する。
+            let collection_id = Self::next_collection_id();
+            let next_collection_id = collection_id.checked_add(1)
+                .ok_or("Error: Overflow happened when trying to register a new collection")?;
+
+            <Collections<T>>::insert(collection_id, Collection { owner: sender.clone(), name });
+            <NextCollectionId<T>>::put(next_collection_id);
+
+            Self::deposit_event(RawEvent::CollectionCreated(sender, collection_id));
+
+            Ok(())
+        }
+
+        // 自分のコレクションに自分のkittyを加える関数。kittyは一つのコレクションにしか入れられない。
+        fn add_to_collection(origin, collection_id: u32, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：コレクションとkittyの両方が自分のものであることを確認する。
+            let collection = Self::collection(collection_id).ok_or("Error: this collection does not exist")?;
+            ensure!(collection.owner == sender, "Error: you have no ownership to this collection");
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership to this kitty");
+
+            ensure!(Self::collection_of(kitty_id).is_none(), "Error: this kitty is already in a collection");
+
+            <KittyCollection<T>>::insert(kitty_id, collection_id);
+
+            Self::deposit_event(RawEvent::KittyAddedToCollection(collection_id, kitty_id));
+
+            Ok(())
+        }
+
+        // 自分のkittyをコレクションから外す関数。
+        fn remove_from_collection(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership to this kitty");
+
+            let collection_id = Self::collection_of(kitty_id).ok_or("Error: this kitty is not in any collection")?;
+
+            <KittyCollection<T>>::remove(kitty_id);
+
+            Self::deposit_event(RawEvent::KittyRemovedFromCollection(collection_id, kitty_id));
+
+            Ok(())
+        }
+
+        // 自分のkittyにタグをつける関数。同じタグは二つつけられない。
+        fn add_tag(origin, kitty_id: KittyId<T::Hash>, tag: Vec<u8>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership to this kitty");
+
+            // Verify first, write lastの原則：タグの長さと数が上限に収まり、重複していないことを確認する。
+            ensure!(!tag.is_empty(), "Error: the tag must not be empty");
+            ensure!(tag.len() <= MAX_TAG_LENGTH, "Error: the tag is too long");
+            let mut tags = Self::tags_of(kitty_id);
+            ensure!(tags.len() < MAX_TAGS_PER_KITTY, "Error: this kitty already has too many tags");
+            ensure!(!tags.contains(&tag), "Error: this kitty already has this tag");
+
+            tags.push(tag);
+            <KittyTags<T>>::insert(kitty_id, tags);
+
+            Ok(())
+        }
+
+        // 自分のkittyからタグを外す関数。
+        fn remove_tag(origin, kitty_id: KittyId<T::Hash>, tag: Vec<u8>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+            ensure!(owner == sender, "Error: you have no ownership to this kitty");
+
+            let mut tags = Self::tags_of(kitty_id);
+            let position = tags.iter().position(|t| *t == tag).ok_or("Error: this kitty does not have this tag")?;
+            tags.swap_remove(position);
+
+            if tags.is_empty() {
+                <KittyTags<T>>::remove(kitty_id);
+            } else {
+                <KittyTags<T>>::insert(kitty_id, tags);
+            }
+
+            Ok(())
+        }
+
+        // kittyをlikeする関数。同じアカウントが何度likeしても一回分しか数えない。
+        fn like_kitty(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            // Verify first, write lastの原則：likeしたいkittyが存在することを確認する。
+            ensure!(<Kitties<T>>::exists(kitty_id), "Error: invalid kitty id: this kitty does not exist");
+
+            // すでにlikeしているならば何もしない。
+            if Self::has_liked((kitty_id, sender.clone())) {
+                return Ok(());
+            }
+
+            let likes = Self::likes_of(kitty_id).checked_add(1)
+                .ok_or("Error: Overflow happened when counting likes of this kitty")?;
+
+            <Likes<T>>::insert(kitty_id, likes);
+            <HasLiked<T>>::insert((kitty_id, sender.clone()), true);
+
+            Self::deposit_event(RawEvent::Liked(sender, kitty_id));
+
+            Ok(())
+        }
+
+        // kittyへのlikeを取り消す関数。likeしていなければ何もしない。
+        // 焼却されたkittyはlikeの数がもう消えているので、likeしたという印だけを消す。
+        fn unlike_kitty(origin, kitty_id: KittyId<T::Hash>) -> Result {
+            let kitty_id = *kitty_id;
+
+            // Verify first, write lastの原則：正当なユーザーがこの関数を叩いたかを確認する。
+            let sender = ensure_signed(origin)?;
+
+            if !Self::has_liked((kitty_id, sender.clone())) {
+                return Ok(());
+            }
+            if !<Kitties<T>>::exists(kitty_id) {
+                <HasLiked<T>>::remove((kitty_id, sender));
+                return Ok(());
+            }
+
+            let likes = Self::likes_of(kitty_id).checked_sub(1)
+                .ok_or("Error: Underflow happened when counting likes of this kitty")?;
+
+            <Likes<T>>::insert(kitty_id, likes);
+            <HasLiked<T>>::remove((kitty_id, sender.clone()));
+
+            Self::deposit_event(RawEvent::Unliked(sender, kitty_id));
+
+            Ok(())
+        }
+    }
+}
+
+impl <T: Trait> Module<T> {
+
+    // 価格をTOKEN_DECIMALS桁の小数として整形する。フロントエンドやCLIが各自で桁をずらさずに済むようにする。
+    // 例えばTOKEN_DECIMALS = 15のとき、1_500_000_000_000_000は"1.5"、0は"0"になる。
+    #[cfg(feature = "std")]
+    pub fn format_price(price: T::Balance) -> String {
+        Self::_format_with_decimals(&format!("{:?}", price), TOKEN_DECIMALS)
+    }
+
+    // 10進数の整数の文字列`digits`を、`decimals`桁の小数として整形する。末尾の0は省く。
+    #[cfg(feature = "std")]
+    fn _format_with_decimals(digits: &str, decimals: usize) -> String {
+        if decimals == 0 {
+            return digits.to_string();
+        }
+
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (integer, fraction) = padded.split_at(padded.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            integer.to_string()
+        } else {
+            format!("{}.{}", integer, fraction)
+        }
+    }
+
+    // UIのバッジに表示するための世代を返す。保存されている世代は変えずに、MAX_DISPLAY_GEN以上は頭打ちにする。
+    pub fn display_gen(gen: u64) -> u32 {
+        cmp::min(gen, u64::from(MAX_DISPLAY_GEN)) as u32
+    }
+
+    // 両親のDNAを乱数`random`に従って混ぜ合わせ、子供のDNAを返す。
+    // どちらの親を先に渡しても同じ子供になるように、混ぜる前に両親のDNAを並べ替える。
+    // MaxMutationDeltaが0でなければ、一部のバイトは親から受け継いだ値からMaxMutationDelta以内だけずれる（変異）。
+    pub fn mix_dna(dna_1: T::Hash, dna_2: T::Hash, random: T::Hash) -> T::Hash {
+        let (dna_1, dna_2) = if dna_1.as_ref() <= dna_2.as_ref() { (dna_1, dna_2) } else { (dna_2, dna_1) };
+
+        // 初期値として片親のDNAをコピー
+        let mut final_dna = dna_1;
+
+        // 変異の大きさを決める乱数。
+        let max_delta = Self::max_mutation_delta();
+        let mutation = T::DnaHashing::hash(random.as_ref());
+
+        // DNAをシェイキング！
+        for (i, ((dna_2_element, r), m)) in dna_2.as_ref().iter().zip(random.as_ref().iter()).zip(mutation.as_ref().iter()).enumerate() {
+            if r % 2 == 0 {
+                final_dna.as_mut()[i] = *dna_2_element;
+            }
+
+            // 乱数のビット1から3が全て0のとき（1/8の確率）に変異させる。ビット4でずらす向きを決め、0と255で頭打ちにする。
+            if max_delta > 0 && (r >> 1) & 0x07 == 0 {
+                // max_deltaが255でも割る数が溢れないよう、u16で計算する。
+                let delta = (u16::from(*m) % (u16::from(max_delta) + 1)) as u8;
+                let gene = final_dna.as_ref()[i];
+                final_dna.as_mut()[i] = if r & 0x10 == 0 { gene.saturating_add(delta) } else { gene.saturating_sub(delta) };
+            }
+        }
+
+        final_dna
+    }
+
+    // kittyを識別する値だけ（dna、世代、両親）から計算した指紋を返す。priceのように変わる値は含めない。
+    // オフチェーンのインデクサーが、reorgをまたいで同じkittyかどうかを見分けるのに使う。両親の順番には依存しない。
+    pub fn fingerprint(kitty: &Kitty<T::Hash, T::Balance, T::BlockNumber>) -> [u8; 32] {
+        let parents = kitty.parents.map(|(parent_1, parent_2)| {
+            if parent_1.as_ref() <= parent_2.as_ref() { (parent_1, parent_2) } else { (parent_2, parent_1) }
+        });
+
+        (kitty.dna, kitty.gen, parents).using_encoded(runtime_io::blake2_256)
+    }
+
+    // dnaから、kittyの絵を描くための8つのパラメータを決める。どのクライアントも同じ絵を描けるように、対応はここで決めておく。
+    // dnaを先頭から4バイトずつbig endianのu32として読み、次の範囲に収める（上限は含まない）。
+    // [0] 体型 0..16、[1] 目 0..16、[2] 口 0..16、[3] 主な色 0..0x1000000（RGB）、[4] 二つ目の色 0..0x1000000（RGB）、
+    // [5] 模様 0..32、[6] 装飾品 0..64、[7] 背景 0..16
+    pub fn portrait_seed(dna: T::Hash) -> [u32; 8] {
+        const RANGES: [u32; 8] = [16, 16, 16, 0x100_0000, 0x100_0000, 32, 64, 16];
+
+        let mut seed = [0u32; 8];
+        for (i, chunk) in dna.as_ref().chunks(4).take(8).enumerate() {
+            let value = chunk.iter().fold(0u32, |value, byte| (value << 8) | u32::from(*byte));
+            seed[i] = value % RANGES[i];
+        }
+        seed
+    }
+
+    // 二匹のkittyが親を共有しているかどうかを返す。どちらかがgen 0（親がいない）ならば共有していない。
+    pub fn share_parent(kitty_id_1: T::Hash, kitty_id_2: T::Hash) -> bool {
+        Self::_parents_overlap(&Self::kitty(kitty_id_1), &Self::kitty(kitty_id_2))
+    }
+
+    fn _parents_overlap(kitty_1: &Kitty<T::Hash, T::Balance, T::BlockNumber>, kitty_2: &Kitty<T::Hash, T::Balance, T::BlockNumber>) -> bool {
+        match (kitty_1.parents, kitty_2.parents) {
+            (Some((a1, b1)), Some((a2, b2))) => a1 == a2 || a1 == b2 || b1 == a2 || b1 == b2,
+            _ => false,
+        }
+    }
+
+    // 少なくとも片親を共有するkitty（兄弟姉妹）のidを返す。runtime APIから呼ぶための読み出し専用の関数。
+    // 一匹目の親の子供のリストに二匹目の親の子供のリストを続けたものの`[start, start + limit)`番目を読み、
+    // そのうち`kitty_id`自身と焼却されたkitty、両方のリストに載っている子供の二度目を除いて返す。
+    // そのため返す数は`limit`より少なくなりうる。gen 0のkittyには親がいないので、兄弟姉妹もいない。
+    pub fn siblings_of(kitty_id: T::Hash, start: u64, limit: u64) -> Vec<T::Hash> {
+        let (parent_1, parent_2) = match Self::kitty(kitty_id).parents {
+            Some(parents) => parents,
+            None => return Vec::new(),
+        };
+
+        let count_1 = Self::children_count(parent_1);
+        let count = count_1.saturating_add(Self::children_count(parent_2));
+        Self::_page(start, limit, count)
+            .filter_map(|i| if i < count_1 {
+                Some(Self::child_of_by_index((parent_1, i)))
+            } else {
+                let child = Self::child_of_by_index((parent_2, i - count_1));
+                // 両親とも同じ子供は、一匹目の親のリストで返しているので飛ばす。
+                match Self::kitty(child).parents {
+                    Some((p1, p2)) if p1 == parent_1 || p2 == parent_1 => None,
+                    _ => Some(child),
+                }
+            })
+            .filter(|sibling| *sibling != kitty_id && <Kitties<T>>::exists(sibling))
+            .collect()
+    }
+
+    // `root`からChildrenOfを下にたどって、(親, 子)の辺を幅優先で返す。runtime APIから呼ぶための読み出し専用の関数。
+    // たどるkittyは`root`を含めて最大`max_nodes`匹（MAX_PAGE_SIZEで頭打ち）で、そこで打ち切る。
+    // 両親とも系図の中にいる子供は、辺は両方返すが一度しかたどらない。循環はないはずだが、同じ理由で無限ループにもならない。
+    pub fn lineage_edges(root: T::Hash, max_nodes: u32) -> Vec<(T::Hash, T::Hash)> {
+        let max_nodes = cmp::min(u64::from(max_nodes), MAX_PAGE_SIZE) as usize;
+        let mut edges = Vec::new();
+        if max_nodes == 0 {
+            return edges;
+        }
+
+        let mut visited = Vec::new();
+        visited.push(root);
+        let mut next = 0;
+        while next < visited.len() {
+            let parent = visited[next];
+            next += 1;
+
+            for i in 0..Self::children_count(parent) {
+                let child = Self::child_of_by_index((parent, i));
+                if !visited.contains(&child) {
+                    if visited.len() >= max_nodes {
+                        return edges;
+                    }
+                    visited.push(child);
+                }
+                edges.push((parent, child));
+            }
+        }
+
+        edges
+    }
+
+    // 次に生成されるkittyのidの計算に使われるnonceを返す。runtime APIから呼ぶための読み出し専用の関数。
+    // create_kittyで作られるkittyのidは`(T::Randomness::random_seed(), 所有者, nonce)`をエンコードしてHashingでハッシュしたもの。
+    // random_seedはそのextrinsicを含むブロックのものなので、クライアントが予測できるのは取り込まれるブロックの乱数が分かっている場合に限る。
+    // また、そのハッシュ値が焼却されたkittyのidに当たった場合は、_unretired_idが焼却されていないidになるまで（最大MAX_REHASH_ATTEMPTS回）
+    // ハッシュし直すので、実際のidはそのハッシュ値とは限らない。
+    pub fn current_nonce() -> u64 {
+        <Nonce<T>>::get()
+    }
+
+    // kittyを返す。runtime APIから呼ぶための読み出し専用の関数。
+    // Kittiesは存在しないキーに対してDefaultを返すので、所有者の存在とidの一致を両方確認してから返す。
+    // idが一致しない場合はストレージが壊れているので、偽のkittyを返さずにNoneとする。
+    pub fn get_kitty(kitty_id: T::Hash) -> Option<Kitty<T::Hash, T::Balance, T::BlockNumber>> {
+        if !<KittyOwner<T>>::exists(kitty_id) {
+            return None;
+        }
+
+        let kitty = Self::kitty(kitty_id);
+        if kitty.id != kitty_id {
+            return None;
+        }
+
+        Some(kitty)
+    }
+
+    // 渡されたidのkittyを同じ順番で返す。存在しないidの位置はNoneになる。
+    // 一回の呼び出しの計算量を抑えるため、MAX_PAGE_SIZE個を超えたidは読まずに捨てる。
+    pub fn kitties_by_ids(kitty_ids: Vec<T::Hash>) -> Vec<Option<Kitty<T::Hash, T::Balance, T::BlockNumber>>> {
+        kitty_ids.into_iter()
+            .take(MAX_PAGE_SIZE as usize)
+            .map(Self::get_kitty)
+            .collect()
+    }
+
+    // kittyとその所有者、like数、dnaの表示名、今のhappiness、タグ、通貨のヒントをまとめたカードを返す。runtime APIから呼ぶための読み出し専用の関数。
+    pub fn kitty_card(kitty_id: T::Hash) -> Option<KittyCard<T::AccountId, T::Hash, T::Balance, T::BlockNumber>> {
+        let kitty = Self::get_kitty(kitty_id)?;
+        let owner = Self::owner_of(kitty_id)?;
+        let dna_name = Self::dna_name(kitty.dna);
+        let happiness = Self::current_happiness(&kitty, <system::Module<T>>::block_number());
+
+        Some(KittyCard {
+            kitty,
+            owner,
+            likes: Self::likes_of(kitty_id),
+            dna_name,
+            happiness,
+            tags: Self::tags_of(kitty_id),
+            currency_hint: Self::currency_hint_of(kitty_id),
+        })
+    }
+
+    // `who`が`actions`を順に実行したら成功するかを試し、最初に失敗した操作の番号とエラーメッセージを返す。
+    // 操作は本物のdispatchableで実行するので、前の操作の結果（生まれたkittyや払った手数料）が後の操作に反映される。
+    // runtime APIの呼び出しでの状態の変更は捨てられるので、runtime APIからだけ呼ぶこと。ブロックの中で呼べば本当に実行されてしまう。
+    // 一回の呼び出しの計算量を抑えるため、MAX_PAGE_SIZE個を超えた操作は試さない。
+    pub fn validate_sequence(who: T::AccountId, actions: Vec<Action<T::AccountId, T::Hash, T::Balance>>) -> rstd::result::Result<(), (u32, Vec<u8>)> {
+        for (i, action) in actions.into_iter().take(MAX_PAGE_SIZE as usize).enumerate() {
+            let origin: T::Origin = system::RawOrigin::Signed(who.clone()).into();
+            let result = match action {
+                Action::Create => Self::create_kitty(origin),
+                Action::Breed(kitty_id_1, kitty_id_2) => Self::breed_kitty(origin, kitty_id_1, kitty_id_2),
+                Action::SetPrice(kitty_id, new_price) => Self::set_price(origin, kitty_id, new_price),
+                Action::Transfer(to, kitty_id) => Self::transfer(origin, to, kitty_id),
+            };
+            result.map_err(|error| (i as u32, error.as_bytes().to_vec()))?;
+        }
+
+        Ok(())
+    }
+
+    // kittyのために使われているストレージのおおよそのバイト数を返す。runtime APIから呼ぶための読み出し専用の関数。
+    // 一匹あたりに必ず書き込まれるエントリの大きさに個体数を掛けた見積もりで、ストレージを走査はしない。
+    // 出品やlike、売却履歴のように一部のkittyにしかないエントリは数えない。
+    pub fn estimated_storage_bytes() -> u64 {
+        // mapのエントリはキーのハッシュ値（32バイト）の下に値が書き込まれる。
+        const STORAGE_KEY_BYTES: u64 = 32;
+
+        let hash_bytes = T::Hash::default().encode().len() as u64;
+        let account_bytes = T::AccountId::default().encode().len() as u64;
+        let index_bytes = 0u64.encode().len() as u64;
+        // 両親がいる方が大きいので、繁殖で生まれたkittyの大きさで見積もる。
+        let kitty_bytes = Kitty {
+            parents: Some((T::Hash::default(), T::Hash::default())),
+            ..Default::default()
+        }.encode().len() as u64;
+
+        // Kitties, KittyOwner, AllKittiesArray, AllKittiesIndex, OwnedKittiesArray, OwnedKittiesIndex, KittyMinter, DnaToKittiesの順。
+        // DnaToKittiesはdnaごとのリストだが、一匹あたりidが一つ増えるものとして数える。
+        let values = [
+            kitty_bytes, account_bytes, hash_bytes, index_bytes, hash_bytes, index_bytes, account_bytes, hash_bytes,
+        ];
+        let per_kitty = values.iter().fold(0u64, |total, value| total + STORAGE_KEY_BYTES + value);
+
+        // CreationLogは焼却されたkittyの分も残るので、生まれた数に掛けて別に数える。
+        let per_creation = STORAGE_KEY_BYTES + hash_bytes;
+
+        // 見積もりなので頭打ちで構わない。
+        Self::all_kitties_count().saturating_mul(per_kitty)
+            .saturating_add(Self::creation_count().saturating_mul(per_creation))
+    }
+
+    // チェーン全体のkittiesの統計を返す。runtime APIから呼ぶための読み出し専用の関数。ほとんどがキャッシュされた値なので安い。
+    // 出品中の最安値を求め直している途中ならば、続きの出品を最大MAX_PAGE_SIZE件だけ読むので、残りがあれば近似値になる。
+    pub fn collection_stats() -> CollectionStats<T::Balance> {
+        let floor_price = match Self::floor_rescan() {
+            Some((cursor, partial)) => Self::_min_listed_price(partial, Self::_page(cursor, MAX_PAGE_SIZE, Self::for_sale_count())),
+            None => Self::floor_price(),
+        };
+
+        CollectionStats {
+            total_supply: Self::all_kitties_count(),
+            gen_zero_count: Self::gen_zero_count(),
+            bred_count: Self::bred_count(),
+            genesis_count: Self::genesis_count(),
+            for_sale_count: Self::for_sale_count(),
+            floor_price,
+            highest_gen: Self::highest_gen(),
+        }
+    }
+
+    // `who`が所有するkittiesを集計して返す。runtime APIから呼ぶための読み出し専用の関数。
+    // 一回の呼び出しの計算量を抑えるため、集計するのは所有リストの先頭のMAX_PAGE_SIZE匹までにする。
+    pub fn account_summary(who: T::AccountId) -> AccountSummary<T::Balance> {
+        let owned = Self::owned_kitty_count(&who);
+        let mut summary = AccountSummary {
+            owned,
+            truncated: owned > MAX_PAGE_SIZE,
+            ..Default::default()
+        };
+
+        for i in Self::_page(0, MAX_PAGE_SIZE, owned) {
+            let kitty = Self::kitty(Self::kitty_of_owner_by_index((who.clone(), i)));
+            summary.highest_gen = cmp::max(summary.highest_gen, kitty.gen);
+            if Self::is_listed(&kitty) {
+                // 件数はMAX_PAGE_SIZEで抑えているので素の+で構わない。合計額は表示用なので頭打ちで構わない。
+                summary.listed += 1;
+                summary.listed_value = summary.listed_value.saturating_add(kitty.price);
+            }
+        }
+
+        summary
+    }
+
+    // `who`の所有リストの通し番号`[start, start + limit)`にあるkittyのうち、出品中のものの価格の合計を返す。
+    // 出品していないkittyにはチェーン上の値段がないので数えない。合計がoverflowすれば頭打ちにする。
+    // 一度に見るのは最大MAX_PAGE_SIZE匹までなので、全体の合計はページを進めて足し合わせる。
+    pub fn portfolio_value(who: T::AccountId, start: u64, limit: u64) -> T::Balance {
+        Self::_page(start, limit, Self::owned_kitty_count(&who))
+            .map(|i| Self::kitty(Self::kitty_of_owner_by_index((who.clone(), i))))
+            .filter(|kitty| Self::is_listed(kitty))
+            .fold(Zero::zero(), |total: T::Balance, kitty| total.saturating_add(kitty.price))
+    }
+
+    // ブロック`now`でのkittyのhappinessを計算する。ストレージには書き込まない。
+    // 最後にご飯をもらってからHappinessDecayPeriodのブロック数が経つごとに1ずつ減り、0で止まる。
+    pub fn current_happiness(kitty: &Kitty<T::Hash, T::Balance, T::BlockNumber>, now: T::BlockNumber) -> u8 {
+        let period = Self::happiness_decay_period();
+        if period.is_zero() {
+            return MAX_HAPPINESS;
+        }
+
+        // last_fed_atが未来を指すことはないが、念のため経過時間は0として扱う。
+        let elapsed = now.checked_sub(&kitty.last_fed_at).unwrap_or_else(Zero::zero);
+        let decay = cmp::min(<T::BlockNumber as As<u64>>::as_(elapsed / period), u64::from(MAX_HAPPINESS));
+        MAX_HAPPINESS - decay as u8
+    }
+
+    // kittyが出品中かどうかを返す。出品中かどうかの判断は全てここで行う。
+    // このモジュールではprice != 0が出品中を意味し、ForSaleArrayに載っているkittyと常に一致させている。
+    pub fn is_listed(kitty: &Kitty<T::Hash, T::Balance, T::BlockNumber>) -> bool {
+        !kitty.price.is_zero()
+    }
+
+    // kittyの売却状態を返す。runtime APIから呼ぶための読み出し専用の関数。
+    pub fn sale_status(kitty_id: T::Hash) -> SaleStatus<T::Balance> {
+        if !<Kitties<T>>::exists(kitty_id) {
+            return SaleStatus::Unknown;
+        }
+
+        let kitty = Self::kitty(kitty_id);
+        if Self::is_listed(&kitty) {
+            SaleStatus::Listed(kitty.price)
+        } else {
+            SaleStatus::Idle
+        }
+    }
+
+    // `who`が売りに出ている`kitty_id`を買っても、アカウントが消えない（existential depositが残る）かを返す。
+    // runtime APIから呼ぶための読み出し専用の関数。存在しないkittyや売りに出ていないkittyにはfalseを返す。
+    pub fn can_afford(who: T::AccountId, kitty_id: T::Hash) -> bool {
+        let price = match Self::sale_status(kitty_id) {
+            SaleStatus::Listed(price) => price,
+            _ => return false,
+        };
+
+        price.checked_add(&<balances::Module<T>>::existential_deposit())
+            .map_or(false, |needed| <balances::Module<T>>::free_balance(&who) >= needed)
+    }
+
+    // dnaから特徴のビットマスクを計算する。
+    // dnaのi番目のバイトの最上位ビットが立っていれば、i番目の特徴を持つものとする（最大64個）。
+    pub fn trait_flags_of(dna: &T::Hash) -> u64 {
+        dna.as_ref().iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, byte)| **byte & 0x80 != 0)
+            .fold(0u64, |flags, (i, _)| flags | (1 << i))
+    }
+
+    // kittyの珍しさの段階を返す。0がふつうで、3が最も珍しい。
+    // 特徴はdnaの各バイトの最上位ビットなので、ランダムなdnaでは32個中16個前後になる。多く持つほど珍しい。
+    pub fn rarity_tier(kitty: &Kitty<T::Hash, T::Balance, T::BlockNumber>) -> u32 {
+        match kitty.trait_flags.count_ones() {
+            0..=19 => 0,
+            20..=23 => 1,
+            24..=27 => 2,
+            _ => 3,
+        }
+    }
+
+    // 通し番号`start`から`limit`匹の範囲で、`required`の特徴を全て持つkittyを返す。runtime APIから呼ぶための読み出し専用の関数。
+    pub fn find_by_traits(required: u64, start: u64, limit: u64) -> Vec<T::Hash> {
+        Self::_all_kitty_ids(start, limit).into_iter()
+            .filter(|kitty_id| Self::kitty(kitty_id).trait_flags & required == required)
+            .collect()
+    }
+
+    // 全kittiesの通し番号`[start, start + limit)`の範囲でlikeされているkittyを、likeの多い順に返す。
+    // runtime APIから呼ぶための読み出し専用の関数。一回で読むのは最大MAX_PAGE_SIZE匹なので、全体の順位はクライアントがページを集めて決める。
+    pub fn most_liked(start: u64, limit: u64) -> Vec<(T::Hash, u32)> {
+        let mut liked: Vec<(T::Hash, u32)> = Self::_all_kitty_ids(start, limit).into_iter()
+            .map(|kitty_id| (kitty_id, Self::likes_of(kitty_id)))
+            .filter(|(_, likes)| *likes > 0)
+            .collect();
+
+        liked.sort_by(|a, b| b.1.cmp(&a.1));
+        liked
+    }
+
+    // 列挙する範囲`[start, start + limit)`を、`count`個の要素を持つリストに収まるように切り詰める。
+    // 列挙系の関数はすべてこれを通すことで、空のリストや範囲外の添字でDefaultの値を読まないようにする。
+    // `limit`はMAX_PAGE_SIZEで頭打ちにするので、どの列挙もO(MAX_PAGE_SIZE)で終わる。
+    fn _page(start: u64, limit: u64, count: u64) -> rstd::ops::Range<u64> {
+        let end = cmp::min(start.saturating_add(cmp::min(limit, MAX_PAGE_SIZE)), count);
+        cmp::min(start, end)..end
+    }
+
+    // 全kittiesの通し番号`[start, start + limit)`の範囲のidを返す。kittyが一匹もいなければ空になる。
+    fn _all_kitty_ids(start: u64, limit: u64) -> Vec<T::Hash> {
+        Self::_page(start, limit, Self::all_kitties_count())
+            .map(|i| Self::kitty_by_index(i))
+            .collect()
+    }
+
+    // kittyが生まれてからMintProtectionのブロック数が経っていることを確認する。
+    fn _ensure_mint_protection_over(kitty: &Kitty<T::Hash, T::Balance, T::BlockNumber>) -> Result {
+        let protected_until = kitty.minted_at.checked_add(&Self::mint_protection())
+            .ok_or("Error: Overflow happened when calculating the mint protection period")?;
+        ensure!(<system::Module<T>>::block_number() >= protected_until,
+            "Error: MintProtectionActive: this kitty was minted too recently to be listed");
+        Ok(())
+    }
+
+    // `owner`が所有するkittiesのうち、所有リストの`[start, start + limit)`番目を返す。runtime APIから呼ぶための読み出し専用の関数。
+    // `start`が所有数を超えていれば空を返す。
+    pub fn owned_kitties_paged(owner: T::AccountId, start: u64, limit: u64) -> Vec<(T::Hash, Kitty<T::Hash, T::Balance, T::BlockNumber>)> {
+        Self::_page(start, limit, Self::owned_kitty_count(&owner))
+            .map(|i| Self::kitty_of_owner_by_index((owner.clone(), i)))
+            .map(|kitty_id| (kitty_id, Self::kitty(kitty_id)))
+            .collect()
+    }
+
+    // 最近生まれた最大`limit`匹のkittyを新しい順に返す。runtime APIから呼ぶための読み出し専用の関数。
+    // kittyはCreationLogの末尾に追記されていくので、末尾から読めばよい。
+    // 読むのは最後の`limit`件だけで、そのうち焼却されたkittyは飛ばすので、返す数は`limit`より少なくなりうる。
+    pub fn recent_kitties(limit: u64) -> Vec<(T::Hash, Kitty<T::Hash, T::Balance, T::BlockNumber>)> {
+        let count = Self::creation_count();
+        // saturating_subにしておけば、limitがkittiesの数より大きくても先頭から読むだけで済む。
+        let start = count.saturating_sub(cmp::min(limit, MAX_PAGE_SIZE));
+        Self::_page(start, count - start, count)
+            .rev()
+            .map(|i| Self::created_by_index(i))
+            .filter(|kitty_id| <Kitties<T>>::exists(kitty_id))
+            .map(|kitty_id| (kitty_id, Self::kitty(kitty_id)))
+            .collect()
+    }
+
+    // SpotlightPeriodごとに一匹ずつ選ばれる、今日のkittyを返す。runtime APIから呼ぶための読み出し専用の関数。
+    // 期間の番号のハッシュ値を全kittiesの数で割った余りを通し番号にするので、同じ期間の間は（kittyが増減しない限り）同じkittyが選ばれる。
+    // 焼却はSwap and Popで詰めるので、AllKittiesArrayの[0, AllKittiesCount)に空きはない。kittyがいなければNone。
+    pub fn kitty_of_the_day() -> Option<T::Hash> {
+        let count = Self::all_kitties_count();
+        let period = Self::spotlight_period();
+        if count == 0 || period.is_zero() {
+            return None;
+        }
+
+        let epoch = <system::Module<T>>::block_number() / period;
+        let hash = (b"substratekitties/spotlight", epoch).using_encoded(<T as system::Trait>::Hashing::hash);
+        let seed = hash.as_ref().iter().take(8).fold(0u64, |seed, byte| (seed << 8) | u64::from(*byte));
+        Some(Self::kitty_by_index(seed % count))
+    }
+
+    // 出品中のkittyの通し番号`[start, start + limit)`の範囲のidとpriceを返す。runtime APIから呼ぶための読み出し専用の関数。
+    pub fn marketplace(start: u64, limit: u64) -> Vec<(T::Hash, T::Balance)> {
+        Self::_page(start, limit, Self::for_sale_count())
+            .map(|i| Self::for_sale_by_index(i))
+            .map(|kitty_id| (kitty_id, Self::kitty(kitty_id).price))
+            .collect()
+    }
+
+    // 出品中のkittyの通し番号`[start, start + limit)`の範囲で、`required`の特徴を全て持つ最も安いkittyと、その価格を返す。
+    // 範囲に一匹もなければNone。runtime APIから呼ぶための読み出し専用の関数。全体の最安値はページを順に読んで比べる。
+    pub fn cheapest_for_sale(required: u64, start: u64, limit: u64) -> Option<(T::Hash, T::Balance)> {
+        Self::_page(start, limit, Self::for_sale_count())
+            .map(|i| Self::kitty(Self::for_sale_by_index(i)))
+            .filter(|kitty| kitty.trait_flags & required == required)
+            .fold(None, |cheapest: Option<(T::Hash, T::Balance)>, kitty| match cheapest {
+                Some((_, price)) if price <= kitty.price => cheapest,
+                _ => Some((kitty.id, kitty.price)),
+            })
+    }
+
+    // kittyを出品中のリストに加える。すでに加えられていれば何もしない。
+    fn _add_to_for_sale(kitty_id: T::Hash) -> Result {
+        if <ForSaleIndex<T>>::exists(kitty_id) {
+            return Ok(());
+        }
+
+        let for_sale_count = Self::for_sale_count();
+        let new_for_sale_count = for_sale_count.checked_add(1)
+            .ok_or("Error: Overflow happened when trying to list a new kitty")?;
+
+        <ForSaleArray<T>>::insert(for_sale_count, kitty_id);
+        <ForSaleIndex<T>>::insert(kitty_id, for_sale_count);
+        <ForSaleCount<T>>::put(new_for_sale_count);
+
+        Ok(())
+    }
+
+    // kittyを出品中のリストからSwap and Popで取り除く。リストになければ何もしない。
+    // 出品の取り下げは全てここを通るので、出品に添えられた通貨のヒントもここで消す。
+    fn _remove_from_for_sale(kitty_id: T::Hash) -> Result {
+        if !<ForSaleIndex<T>>::exists(kitty_id) {
+            <ListingCurrencyHint<T>>::remove(kitty_id);
+            return Ok(());
+        }
+
+        let new_for_sale_count = Self::for_sale_count().checked_sub(1)
+            .ok_or("Error: Underflow happened when trying to unlist a kitty")?;
+        let kitty_index = <ForSaleIndex<T>>::get(kitty_id);
+
+        if kitty_index != new_for_sale_count {
+            let last_kitty_id = <ForSaleArray<T>>::get(new_for_sale_count);
+            <ForSaleArray<T>>::insert(kitty_index, last_kitty_id);
+            <ForSaleIndex<T>>::insert(last_kitty_id, kitty_index);
+
+            // 最安値を求め直している途中で、まだ読んでいない出品が読み終えた位置に移ったら、その値段をここで含めておく。
+            if let Some((cursor, partial)) = Self::floor_rescan() {
+                if kitty_index < cursor {
+                    let partial = Self::_min_listed_price(partial, kitty_index..kitty_index + 1);
+                    <FloorRescan<T>>::put((cursor, partial));
+                }
+            }
+        }
+
+        <ForSaleArray<T>>::remove(new_for_sale_count);
+        <ForSaleIndex<T>>::remove(kitty_id);
+        <ForSaleCount<T>>::put(new_for_sale_count);
+        <ListingCurrencyHint<T>>::remove(kitty_id);
+
+        Ok(())
+    }
+
+    // あるkittyのpriceが`old_price`から`new_price`に変わった後に、出品中の最安値のキャッシュを更新する。
+    // 最安値のkittyが取り下げられたり値上げされたりしたときは、その場で出品中のリストを読み直さずに、
+    // FloorRescanで求め直しを始めて、on_finaliseで少しずつ読み進める。
+    fn _update_floor_price(old_price: T::Balance, new_price: T::Balance) {
+        let left = |floor: Option<T::Balance>| !old_price.is_zero() && floor == Some(old_price) && (new_price.is_zero() || new_price > old_price);
+        let lowered = |floor: Option<T::Balance>| !new_price.is_zero() && floor.map_or(true, |floor| new_price < floor);
+
+        if let Some((cursor, partial)) = Self::floor_rescan() {
+            // 求め直している途中の最安値がいなくなったら、最初から読み直す。
+            if left(partial) {
+                <FloorRescan<T>>::put((0, None));
+            } else if lowered(partial) {
+                // 新しい値段は読み終えた位置にあるかもしれないので、ここで含めておく。
+                <FloorRescan<T>>::put((cursor, Some(new_price)));
+            }
+            return;
+        }
+
+        let floor = Self::floor_price();
+        if left(floor) {
+            <FloorPrice<T>>::kill();
+            <FloorRescan<T>>::put((0, None));
+        } else if lowered(floor) {
+            <FloorPrice<T>>::put(new_price);
+        }
+    }
+
+    // 出品中の最安値を求め直している途中ならば、出品中のリストを最大MAX_PAGE_SIZE件読み進め、読み終えたらキャッシュに書き込む。
+    fn _rescan_floor_price() {
+        let (cursor, partial) = match Self::floor_rescan() {
+            Some(rescan) => rescan,
+            None => return,
+        };
+
+        let count = Self::for_sale_count();
+        let page = Self::_page(cursor, MAX_PAGE_SIZE, count);
+        let end = page.end;
+        let partial = Self::_min_listed_price(partial, page);
+
+        if end < count {
+            <FloorRescan<T>>::put((end, partial));
+            return;
+        }
+
+        <FloorRescan<T>>::kill();
+        match partial {
+            Some(floor) => <FloorPrice<T>>::put(floor),
+            None => <FloorPrice<T>>::kill(),
+        }
+    }
+
+    // 出品中のリストの通し番号`indices`にある出品の値段と`partial`のうち、最も安いものを返す。
+    fn _min_listed_price(partial: Option<T::Balance>, indices: rstd::ops::Range<u64>) -> Option<T::Balance> {
+        indices
+            .map(|i| Self::kitty(Self::for_sale_by_index(i)).price)
+            .filter(|price| !price.is_zero())
+            .chain(partial)
+            .min()
+    }
+
+    // kittyのpriceを更新し、出品の有効期限を管理するヘルパー関数。所有権の確認は呼び出し側で行う。
+    fn _set_price(owner: T::AccountId, kitty_id: T::Hash, new_price: T::Balance) -> Result {
+        // kittyをkitty IDで引き出して、priceを更新して、書き戻す。
+        let mut kitty = Self::kitty(kitty_id);
+        let old_price = kitty.price;
+        kitty.price = new_price;
+
+        // 出品の有効期限を計算する。price == 0は出品の取り下げなので期限も消す。
+        let listing_duration = Self::listing_duration();
+        let expiry = if new_price.is_zero() || listing_duration.is_zero() {
+            None
+        } else {
+            let now = <system::Module<T>>::block_number();
+            Some(now.checked_add(&listing_duration)
+                .ok_or("Error: Overflow happened when calculating the listing expiry")?)
+        };
+
+        // 出品中のリストを更新する。
+        if new_price.is_zero() {
+            Self::_remove_from_for_sale(kitty_id)?;
+        } else {
+            Self::_add_to_for_sale(kitty_id)?;
+        }
+
+        Self::_touch(&mut kitty);
+        <Kitties<T>>::insert(kitty_id, kitty);
+        Self::_update_floor_price(old_price, new_price);
+
+        // 期限切れになるブロックのバケツにkittyを登録する。
+        match expiry {
+            Some(expiry) => {
+                <ListingExpiry<T>>::insert(kitty_id, expiry);
+                <ExpiringAt<T>>::mutate(expiry, |ids| ids.push(kitty_id));
+            },
+            None => <ListingExpiry<T>>::remove(kitty_id),
+        }
+
+        // ブロックチェーンの状態が遷移したので、それを通知するイベントを吐く。
+        Self::deposit_event(RawEvent::PriceSet(owner, kitty_id, new_price));
+
+        Ok(())
+    }
+
+    // gen 0のkittyを生成して`to`に記録し、そのidを返すヘルパー関数。
+    fn _create_kitty(to: T::AccountId, soulbound: bool) -> rstd::result::Result<T::Hash, &'static str> {
+        // nonceを計算する。
+        let nonce = <Nonce<T>>::get();
+
+        // 所有者となるアカウントからnonceと合わせてハッシュ値を計算する。
+        // 「random_hash <--> kitty」は一対一対応している。
+        let random_hash = (T::Randomness::random_seed(), &to, nonce)
+            .using_encoded(<T as system::Trait>::Hashing::hash);
+
+        let random_hash = Self::_unretired_id(random_hash);
+
+        // 計算したrandom_hashが衝突していないことを確認する。
+        ensure!(!<KittyOwner<T>>::exists(random_hash), "the kitty coressponding to this ID already exit!");
+
+        // Verify first, write lastの原則：nonceがoverflowしないことと、このブロックでまだkittyを生み出せることを確認する。
+        let new_nonce = nonce.checked_add(1)
+            .ok_or("Error: Overflow happened when incrementing the nonce")?;
+        let new_mints = Self::_ensure_mint_capacity(1)?;
+
+        // new_kittyを生成する。
+        let new_kitty = Kitty {
+            id: random_hash,
+            dna: random_hash,
+            price: <T::Balance as As<u64>>::sa(0),
+            gen: 0,
+            cooldown_end: <T::BlockNumber as As<u64>>::sa(0),
+            soulbound,
+            trait_flags: 0,
+            minted_at: <T::BlockNumber as As<u64>>::sa(0),
+            parents: None,
+            last_fed_at: <T::BlockNumber as As<u64>>::sa(0),
+            last_active_at: <T::BlockNumber as As<u64>>::sa(0),
+        };
+
+        // 新たに生成されたkittyを記録する。
+        Self::_mint(to, random_hash, new_kitty, false)?;
+        <MintsThisBlock<T>>::put(new_mints);
+
+        // Nonceをインクリメント
+        <Nonce<T>>::put(new_nonce);
+
+        Ok(random_hash)
+    }
+
+    // 世代`gen`のkittyが繁殖した後のクールダウンを返す。
+    // 基本値に(世代 + 1)を掛けるが、係数はMAX_COOLDOWN_FACTORで頭打ちにする。
+    pub fn cooldown_for_gen(gen: u64) -> rstd::result::Result<T::BlockNumber, &'static str> {
+        // 係数はどうせ頭打ちにするので、gen + 1はsaturatingで構わない。
+        let factor = cmp::min(gen.saturating_add(1), MAX_COOLDOWN_FACTOR);
+        Self::breeding_cooldown().checked_mul(&<T::BlockNumber as As<u64>>::sa(factor))
+            .ok_or("Error: Overflow happened when calculating the breeding cooldown")
+    }
+
+    // `amount`の`percent`%を端数切り捨てで返す。`amount * percent`を直接計算するとoverflowしうるので、
+    // 100で割った商と余りに分けて計算する。`percent`が100以下ならば結果は`amount`を超えない。
+    fn _percent_of(amount: T::Balance, percent: u32) -> T::Balance {
+        let hundred = <T::Balance as As<u64>>::sa(100);
+        let percent = <T::Balance as As<u64>>::sa(percent as u64);
+        amount / hundred * percent + amount % hundred * percent / hundred
+    }
+
+    // 買取側`buyer`が支払う売却額`price`を、ロイヤリティ（minterへ）、焼却分、残り（売却側`seller`へ）に分配するヘルパー関数。
+    // 割合での計算の端数は切り捨て、その分は売却側に渡す。失敗しうる確認は全て代金を動かす前に行うので、途中で失敗することはない。
+    fn settle_sale(buyer: &T::AccountId, price: T::Balance, seller: &T::AccountId, minter: Option<T::AccountId>) -> Result {
+        // Verify first, write lastの原則：設定された割合が正しいことを確認する。
+        let royalty_percent = Self::royalty_percent();
+        let burn_percent = Self::burn_percent();
+        ensure!(royalty_percent.saturating_add(burn_percent) <= 100, "Error: royalty and burn percentages add up to more than 100");
+
+        let existential_deposit = <balances::Module<T>>::existential_deposit();
+        let royalty = match minter {
+            // minterが買取側自身ならば支払う必要はなく、売却側自身ならば売却側への支払いにまとめる。
+            Some(ref minter) if minter != buyer && minter != seller => {
+                let royalty = Self::_percent_of(price, royalty_percent);
+                // 消えたアカウントはexistential deposit未満の額では作り直せないので、払えないロイヤリティは売却側に渡す。
+                if royalty < existential_deposit && <balances::Module<T>>::free_balance(minter).is_zero() {
+                    <T::Balance as As<u64>>::sa(0)
+                } else {
+                    royalty
+                }
+            },
+            _ => <T::Balance as As<u64>>::sa(0),
+        };
+        let burn = Self::_percent_of(price, burn_percent);
+        // 割合の合計が100以下であることは確認済みなので、royalty + burnはpriceを超えない。
+        let to_seller = price - royalty - burn;
+
+        // Verify first, write lastの原則：売却側のアカウントが消えていても、受け取る額で作り直せることを確認する。
+        ensure!(to_seller.is_zero() || to_seller >= existential_deposit || !<balances::Module<T>>::free_balance(seller).is_zero(),
+            "Error: the seller's account is gone and the seller's share is too low to recreate it");
+
+        // make_transferは送った額に加えてbalancesモジュールの手数料も差し引くので、支払いごとの負担を先に計算しておく。
+        let zero = <T::Balance as As<u64>>::sa(0);
+        let royalty_cost = match minter {
+            Some(ref minter) if !royalty.is_zero() => Self::_payment_cost(minter, royalty)?,
+            _ => zero,
+        };
+        let seller_cost = if to_seller.is_zero() { zero } else { Self::_payment_cost(seller, to_seller)? };
+        let total_cost = royalty_cost.checked_add(&burn)
+            .and_then(|cost| cost.checked_add(&seller_cost))
+            .ok_or("Error: Overflow happened when calculating the cost of this sale")?;
+
+        // Verify first, write lastの原則：買取側が売却額と手数料の全額を払えることを確認する。
+        let free_balance = <balances::Module<T>>::free_balance(buyer);
+        ensure!(free_balance >= total_cost, "Error: you don't have enough free balance to buy this kitty");
+        // make_transferもslashも、残高がexistential depositを下回ったアカウントを消してしまうので、支払う前に確認する。
+        ensure!(!Self::buyer_keep_alive() || free_balance - total_cost >= existential_deposit,
+            "Error: buying this kitty would leave your balance below the existential deposit");
+        // 消えてよい場合でも、消えるのは最後の支払いでなければならない。途中で消えると残りの支払いができなくなる。
+        // 支払いはロイヤリティ、焼却、売却側の順に行うので、最後の支払いの前まで残高が残ることを確認する。
+        let last_cost = if !seller_cost.is_zero() { seller_cost } else if !burn.is_zero() { burn } else { royalty_cost };
+        ensure!(free_balance - (total_cost - last_cost) >= existential_deposit,
+            "Error: buying this kitty would empty your account before the sale is settled");
+
+        // ここまでで、各支払いの受け取り側と金額、手数料を全て確認したので、以下の支払いは失敗しない。
+        if let Some(ref minter) = minter {
+            if !royalty.is_zero() {
+                <balances::Module<T>>::make_transfer(buyer, minter, royalty)?;
+            }
+        }
+
+        if !burn.is_zero() {
+            <balances::Module<T>>::slash(buyer, burn);
+        }
+
+        if !to_seller.is_zero() {
+            <balances::Module<T>>::make_transfer(buyer, seller, to_seller)?;
+        }
+
+        Ok(())
+    }
+
+    // `dest`に`amount`をmake_transferで送るときに、送り手の残高から差し引かれる額を返す。
+    // 残高が0のアカウントへの送金はアカウントを作り直すので、送金手数料の代わりに作成手数料がかかる。
+    fn _payment_cost(dest: &T::AccountId, amount: T::Balance) -> rstd::result::Result<T::Balance, &'static str> {
+        let fee = if <balances::Module<T>>::free_balance(dest).is_zero() {
+            <balances::Module<T>>::creation_fee()
+        } else {
+            <balances::Module<T>>::transfer_fee()
+        };
+        amount.checked_add(&fee).ok_or("Error: Overflow happened when adding the transfer fee to a payment")
+    }
+
+    // さらに`litter_size`匹が繁殖で生まれても、BredCount <= (GenZeroCount + GenesisCount) * MaxBredPerGenZeroが保たれることを確認する。
+    // 上限の計算がoverflowするほどgen 0のkittyが多ければ、事実上制限はない。
+    fn _ensure_breed_ratio(litter_size: u64) -> Result {
+        let max_bred_per_gen_zero = Self::max_bred_per_gen_zero();
+        if max_bred_per_gen_zero == 0 {
+            return Ok(());
+        }
+
+        let new_bred_count = Self::bred_count().checked_add(litter_size)
+            .ok_or("Error: Overflow happened when counting the new kitty")?;
+        let within_ratio = Self::gen_zero_count().checked_add(Self::genesis_count())
+            .and_then(|seed_count| seed_count.checked_mul(u64::from(max_bred_per_gen_zero)))
+            .map_or(true, |limit| new_bred_count <= limit);
+        ensure!(within_ratio, "Error: BreedRatioExceeded: too many kitties have been bred for the number of gen 0 kitties");
+
+        Ok(())
+    }
+
+    // `sender`の依頼で`kitty_id`を`to`に転送し、転送手数料を`sender`から取って、転送前の所有者を返すヘルパー関数。
+    // 所有者から管理を任されたoperatorも転送できる。
+    fn _paid_transfer(sender: T::AccountId, to: T::AccountId, kitty_id: T::Hash) -> rstd::result::Result<T::AccountId, &'static str> {
+        // Verify first, write lastの原則：転送したいkittyの存在を確認する。
+        let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+        ensure!(Self::_is_owner_or_operator(&owner, &sender), "Error: you have no ownership for this kitty");
+        Self::_ensure_not_adoption_center(&to)?;
+
+        // Verify first, write lastの原則：無償の転送には手数料がかかるので、呼び出し側が払えることを確認する。
+        let transfer_fee = Self::transfer_fee();
+        ensure!(<balances::Module<T>>::free_balance(&sender) >= transfer_fee,
+            "Error: you don't have enough free balance to pay the transfer fee");
+
+        // Verify first, write lastの原則：手数料を払う前に、kittyを転送できることを確認しておく。
+        Self::_ensure_can_transfer(&owner, &to, kitty_id)?;
+
+        // 手数料をFeeCollectorに支払う。支払いが失敗しても、まだ何も書き込んでいない。
+        if !transfer_fee.is_zero() {
+            <balances::Module<T>>::make_transfer(&sender, &Self::fee_collector(), transfer_fee)?;
+        }
+
+        // 転送をする。
+        Self::_transfer_from(owner.clone(), to, kitty_id)?;
+
+        Ok(owner)
+    }
+
+    // rootか、今の管理者からの呼び出しであることを確認する。
+    fn _ensure_root_or_admin(origin: T::Origin) -> Result {
+        match origin.into() {
+            Some(system::RawOrigin::Root) => Ok(()),
+            Some(system::RawOrigin::Signed(ref who)) if Self::admin().as_ref() == Some(who) => Ok(()),
+            _ => Err("Error: only root or the admin can call this function"),
+        }
+    }
+
+    // `who`が`owner`本人か、`owner`から管理を任されたoperatorかどうかを返す。
+    fn _is_owner_or_operator(owner: &T::AccountId, who: &T::AccountId) -> bool {
+        owner == who || Self::is_approved_for_all((owner.clone(), who.clone()))
+    }
+
+    // 二匹の親が今繁殖できることを書き込まずに確認し、クールダウンを更新した後の両親と、この繁殖の手数料を返す。
+    // 手数料 = 基本の手数料 * (1 + 親1の珍しさ + 親2の珍しさ)。払えるかどうかは呼び出し側で確認する。
+    fn _check_parents(breeder: &T::AccountId, partner: &T::AccountId, kitty_id_1: T::Hash, kitty_id_2: T::Hash, now: T::BlockNumber)
+        -> rstd::result::Result<(Kitty<T::Hash, T::Balance, T::BlockNumber>, Kitty<T::Hash, T::Balance, T::BlockNumber>, T::Balance), &'static str>
+    {
+        // Verify first, write lastの原則：繁殖が止められていないことを確認する。どの経路の繁殖もここを通る。
+        ensure!(Self::breeding_paused_until().map_or(true, |until| now >= until), "Error: BreedingPaused: breeding is paused until a later block");
+
+        // 同じkittyを両親にすることはできない。
+        ensure!(kitty_id_1 != kitty_id_2, "Error: a cat can not breed with itself");
+
+        // Verify first, write lastの原則：一匹目は`breeder`の、二匹目は`partner`のkittyであることを確認する。共同所有者も繁殖させられる。
+        // 自分のkitty同士の繁殖では`partner`は`breeder`自身で、他人のkittyとの繁殖では承認した相手になる。
+        ensure!(Self::_can_breed_with(breeder, kitty_id_1), "Error: you have no ownership to this cat 1");
+        ensure!(Self::_can_breed_with(partner, kitty_id_2), "Error: you have no ownership to this cat 2");
+
+        // 親を引き出す。各親のストレージは一度だけ読み、書き戻しも最後に一度だけ行う。
+        // 存在しないkittyはDefaultが返るので、idが一致するかで存在確認を兼ねる。
+        let mut kitty_1 = Self::kitty(kitty_id_1);
+        let mut kitty_2 = Self::kitty(kitty_id_2);
+
+        // Verify first, write lastの原則：kittyの存在確認。
+        ensure!(kitty_1.id == kitty_id_1, "Error: this cat 1 does not exist");
+        ensure!(kitty_2.id == kitty_id_2, "Error: this cat 2 does not exist");
+
+        // Verify first, write lastの原則：売りに出ている親は繁殖できない。繁殖の途中で売れてしまわないよう、先に出品を取り下げてもらう。
+        ensure!(!Self::is_listed(&kitty_1), "Error: ParentListed: this cat 1 is for sale and can not breed");
+        ensure!(!Self::is_listed(&kitty_2), "Error: ParentListed: this cat 2 is for sale and can not breed");
+
+        // 珍しい親ほど手数料が高くなる。
+        let weight = 1 + Self::rarity_tier(&kitty_1) + Self::rarity_tier(&kitty_2);
+        let breeding_fee = Self::breeding_fee().checked_mul(&<T::Balance as As<u64>>::sa(u64::from(weight)))
+            .ok_or("Error: Overflow happened when calculating the breeding fee")?;
+
+        // Verify first, write lastの原則：設定によっては、親を共有するkitty同士の繁殖を禁止する。
+        ensure!(!(Self::forbid_sibling_breeding() && Self::_parents_overlap(&kitty_1, &kitty_2)),
+            "Error: RelatedKitties: these cats share a parent and can not breed");
+
+        // Verify first, write lastの原則：両親ともクールダウンが明けていることを確認する。
+        // 設定によっては、gen 0の親にはクールダウンがない。
+        let exempt_1 = Self::gen_zero_exempt_from_cooldown() && kitty_1.gen == 0;
+        let exempt_2 = Self::gen_zero_exempt_from_cooldown() && kitty_2.gen == 0;
+        ensure!(exempt_1 || kitty_1.cooldown_end <= now, "Error: this cat 1 is still in breeding cooldown");
+        ensure!(exempt_2 || kitty_2.cooldown_end <= now, "Error: this cat 2 is still in breeding cooldown");
+
+        // Verify first, write lastの原則：両親とも生まれてからMinBreedingAgeのブロック数が経っていることを確認する。
+        let min_breeding_age = Self::min_breeding_age();
+        let adult_at_1 = kitty_1.minted_at.checked_add(&min_breeding_age)
+            .ok_or("Error: Overflow happened when calculating the breeding age")?;
+        let adult_at_2 = kitty_2.minted_at.checked_add(&min_breeding_age)
+            .ok_or("Error: Overflow happened when calculating the breeding age")?;
+        ensure!(now >= adult_at_1, "Error: KittyTooYoung: this cat 1 is too young to breed");
+        ensure!(now >= adult_at_2, "Error: KittyTooYoung: this cat 2 is too young to breed");
+
+        // Verify first, write lastの原則：両親ともhappinessが足りていることを確認する。
+        let min_breeding_happiness = Self::min_breeding_happiness();
+        ensure!(Self::current_happiness(&kitty_1, now) >= min_breeding_happiness, "Error: this cat 1 is not happy enough to breed");
+        ensure!(Self::current_happiness(&kitty_2, now) >= min_breeding_happiness, "Error: this cat 2 is not happy enough to breed");
+
+        // 両親それぞれの世代に応じたクールダウン明けのブロックを計算する。クールダウンのない親はそのままにする。
+        if !exempt_1 {
+            kitty_1.cooldown_end = now.checked_add(&Self::cooldown_for_gen(kitty_1.gen)?)
+                .ok_or("Error: Overflow happened when calculating the breeding cooldown")?;
+        }
+        if !exempt_2 {
+            kitty_2.cooldown_end = now.checked_add(&Self::cooldown_for_gen(kitty_2.gen)?)
+                .ok_or("Error: Overflow happened when calculating the breeding cooldown")?;
+        }
+
+        Ok((kitty_1, kitty_2, breeding_fee))
+    }
+
+    // `breeder`の今の繁殖期間の(開始ブロック, その期間の繁殖回数)を返す。期間が過ぎていれば、今のブロックから新しい期間を始める。
+    fn _current_breed_period(breeder: &T::AccountId, now: T::BlockNumber) -> rstd::result::Result<(T::BlockNumber, u32), &'static str> {
+        let (period_start, breeds) = Self::breeds_today(breeder);
+        let period_end = period_start.checked_add(&Self::breed_period())
+            .ok_or("Error: Overflow happened when calculating the breeding period")?;
+        Ok(if now >= period_end { (now, 0) } else { (period_start, breeds) })
+    }
+
+    // `breeder`が二匹の親から子供を作らせ、子供を`recipient`に記録するヘルパー関数。手数料と繁殖回数は`breeder`の分になる。
+    // 一匹目の親は`breeder`の、二匹目の親は`partner`のkittyでなければならない。
+    // `offer_fee`は`breeder`が繁殖の申し込みで予約しておいた手数料で、確認が全て済んだら`partner`に移す。
+    fn _breed(breeder: T::AccountId, partner: T::AccountId, recipient: T::AccountId, kitty_id_1: T::Hash, kitty_id_2: T::Hash,
+        offer_fee: T::Balance) -> Result
+    {
+        // Verify first, write lastの原則：基本の手数料すら払えないアカウントは、親を読んだりidを計算したりする前に弾く。
+        ensure!(<balances::Module<T>>::free_balance(&breeder) >= Self::breeding_fee(),
+            "Error: InsufficientBalance: you don't have enough free balance to pay the breeding fee");
+
+        // Verify first, write lastの原則：両親が繁殖できることと、珍しさに応じた手数料を払えることを確認する。
+        let now = <system::Module<T>>::block_number();
+        let (mut kitty_1, mut kitty_2, breeding_fee) = Self::_check_parents(&breeder, &partner, kitty_id_1, kitty_id_2, now)?;
+        ensure!(<balances::Module<T>>::free_balance(&breeder) >= breeding_fee,
+            "Error: InsufficientBalance: you don't have enough free balance to pay the breeding fee");
+
+        // 一度の繁殖で生まれる子供の数。0は1として扱う。
+        let litter_size = cmp::max(Self::litter_size(), 1) as u64;
+
+        // 子供に振られるidを、一匹ずつ続きのnonceで計算する。
+        let nonce = <Nonce<T>>::get();
+        let new_nonce = nonce.checked_add(litter_size)
+            .ok_or("Error: Overflow happened when incrementing the nonce")?;
+        let child_ids: Vec<T::Hash> = (nonce..new_nonce)
+            .map(|child_nonce| Self::_unretired_id((T::Randomness::random_seed(), &breeder, child_nonce)
+                            .using_encoded(<T as system::Trait>::Hashing::hash)))
+            .collect();
+
+        // Verify first, write lastの原則：アカウントの繁殖回数が期間内の上限に達していないことを確認する。
+        let (period_start, breeds) = Self::_current_breed_period(&breeder, now)?;
+        let max_breeds = Self::max_breeds_per_period();
+        ensure!(max_breeds == 0 || breeds < max_breeds, "Error: BreedQuotaExceeded: you have bred too many times in this period");
+        let new_breeds = breeds.checked_add(1)
+            .ok_or("Error: Overflow happened when counting your breeds in this period")?;
+
+        // Verify first, write lastの原則：繁殖で生まれたkittyの数が、gen 0のkittyの数に対する上限を超えないことを確認する。
+        Self::_ensure_breed_ratio(litter_size)?;
+
+        let child_gen = cmp::max(kitty_1.gen, kitty_2.gen).checked_add(1)
+            .ok_or("Error: Overflow happened when calculating the generation of the child")?;
+
+        // Verify first, write lastの原則：両親の子供のリストに一腹の子供を全て加えられることを確認する。
+        for parent in &[kitty_id_1, kitty_id_2] {
+            Self::children_count(parent).checked_add(litter_size)
+                .ok_or("Error: Overflow happened when counting the children of a parent")?;
+        }
+
+        // Verify first, write lastの原則：子供を一匹残らず記録できることを確認する。一匹でも記録できなければ繁殖全体を取りやめる。
+        // 確認が一つでも失敗した繁殖で手数料を取ってしまわないよう、手数料を取るのは全ての確認が終わった後にする。
+        Self::owned_kitty_count(&recipient).checked_add(litter_size)
+            .ok_or("Error: Overflow happed when trying to register the litter in the recipient's account balance")?;
+        Self::all_kitties_count().checked_add(litter_size)
+            .ok_or("Error: Overflow happened when trying to register the litter")?;
+        for (i, child_id) in child_ids.iter().enumerate() {
+            ensure!(!child_ids[..i].contains(child_id), "Error: the kitty coressponding to this ID already exit!");
+            Self::_ensure_can_mint(&recipient, *child_id)?;
+        }
+        let new_mints = Self::_ensure_mint_capacity(litter_size)?;
+
+        // Verify first, write lastの原則：申し込みの手数料が予約されたまま残っていることを確認する。
+        ensure!(<balances::Module<T>>::reserved_balance(&breeder) >= offer_fee,
+            "Error: the reserved offer fee for this breed is no longer available");
+
+        // 申し込みの手数料を予約から直接`partner`に移す。受け取り側のアカウントが消えていれば、何も書き込まずに失敗する。
+        if !offer_fee.is_zero() {
+            <balances::Module<T>>::repatriate_reserved(&breeder, &partner, offer_fee)?;
+        }
+
+        // 手数料を焼却する。
+        if !breeding_fee.is_zero() {
+            <balances::Module<T>>::slash(&breeder, breeding_fee);
+        }
+
+        // 繁殖が失敗したかどうかを、子供のidとは別に計算した乱数で決める。同じ乱数からは必ず同じ結果になる。
+        let failed = Self::_breed_failed((T::Randomness::random_seed(), &breeder, nonce, b"breed-failure")
+            .using_encoded(<T as system::Trait>::Hashing::hash));
+        if failed {
+            Self::deposit_event(RawEvent::BreedFailed(breeder.clone(), kitty_id_1, kitty_id_2));
+        } else {
+            for child_id in child_ids {
+                // 子供誕生。dnaは子供ごとのidをDnaHashingでハッシュした乱数で、両親から独立に混ぜる。
+                let new_kitty = Kitty {
+                    id: child_id,
+                    dna: Self::mix_dna(kitty_1.dna, kitty_2.dna, T::DnaHashing::hash(child_id.as_ref())),
+                    price: <T::Balance as As<u64>>::sa(0),
+                    gen: child_gen,
+                    cooldown_end: <T::BlockNumber as As<u64>>::sa(0),
+                    soulbound: false,
+                    trait_flags: 0,
+                    minted_at: <T::BlockNumber as As<u64>>::sa(0),
+                    parents: Some((kitty_id_1, kitty_id_2)),
+                    last_fed_at: <T::BlockNumber as As<u64>>::sa(0),
+                    last_active_at: <T::BlockNumber as As<u64>>::sa(0),
+                };
+
+                // 子供の所有権を記録する。
+                Self::_mint(recipient.clone(), child_id, new_kitty, false)?;
+
+                // 両親それぞれの子供のリストに加える。
+                for parent in &[kitty_id_1, kitty_id_2] {
+                    let children_count = Self::children_count(parent);
+                    <ChildrenOf<T>>::insert((*parent, children_count), child_id);
+                    <ChildrenCount<T>>::insert(parent, children_count + 1);
+                }
+
+                Self::deposit_event(RawEvent::Bred(breeder.clone(), child_id, kitty_id_1, kitty_id_2));
+                if recipient != breeder {
+                    Self::deposit_event(RawEvent::BredForGift(breeder.clone(), recipient.clone(), child_id));
+                }
+            }
+            <MintsThisBlock<T>>::put(new_mints);
+        }
+
+        // この期間の繁殖回数を記録する。一腹の子供は何匹でも一回と数える。失敗した繁殖も一回と数える。
+        <BreedsToday<T>>::insert(&breeder, (period_start, new_breeds));
+
+        // クールダウンを更新した両親を、それぞれ一度だけ書き戻す。
+        Self::_touch(&mut kitty_1);
+        Self::_touch(&mut kitty_2);
+        <Kitties<T>>::insert(kitty_id_1, kitty_1);
+        <Kitties<T>>::insert(kitty_id_2, kitty_2);
+
+        // nonce更新
+        <Nonce<T>>::put(new_nonce);
+
+        Ok(())
+    }
+
+    // 乱数`random`に従って、繁殖がBreedFailureChanceBpsの確率で失敗したかどうかを返す。
+    fn _breed_failed(random: T::Hash) -> bool {
+        let chance = Self::breed_failure_chance_bps();
+        if chance == 0 {
+            return false;
+        }
+
+        // 乱数の先頭2バイトを0から9999までの値にする。わずかに偏るが、確率の目安としては十分である。
+        let bytes = random.as_ref();
+        let roll = ((u32::from(bytes[0]) << 8) | u32::from(bytes[1])) % 10_000;
+        roll < chance
+    }
+
+    // 個体数`supply`がMaxTotalSupplyのSupplyWarningPercent%以上かどうかを返す。上限がなければ常にfalse。
+    fn _supply_threshold_reached(supply: u64) -> bool {
+        let max_total_supply = Self::max_total_supply();
+        if max_total_supply == 0 {
+            return false;
+        }
+
+        // 比較にしか使わないので、u64の範囲を超えるほど大きな値は頭打ちで構わない。
+        supply.saturating_mul(100) >= max_total_supply.saturating_mul(u64::from(Self::supply_warning_percent()))
+    }
+
+    // さらに`count`匹のkittyを生み出してもMaxTotalSupplyとMaxMintsPerBlockを超えないことを確認し、このブロックで生み出した後の数を返す。
+    fn _ensure_mint_capacity(count: u64) -> rstd::result::Result<u32, &'static str> {
+        let new_supply = Self::all_kitties_count().checked_add(count)
+            .ok_or("Error: Overflow happened when trying to register a new kitty")?;
+        let max_total_supply = Self::max_total_supply();
+        ensure!(max_total_supply == 0 || new_supply <= max_total_supply,
+            "Error: MaxTotalSupplyReached: no more kitties can be minted on this chain");
+
+        let new_mints = u64::from(Self::mints_this_block()).checked_add(count)
+            .ok_or("Error: Overflow happened when counting the kitties minted in this block")?;
+        let max_mints = Self::max_mints_per_block();
+        ensure!(max_mints == 0 || new_mints <= u64::from(max_mints),
+            "Error: BlockMintLimitReached: too many kitties have been minted in this block");
+
+        // 上限を確認した後なので、上限がなくてもu32に収まる範囲でだけ数える。
+        Ok(cmp::min(new_mints, u64::from(u32::max_value())) as u32)
+    }
+
+    // `to`に`kitty_id`の新たなkittyを記録できるか（idの衝突や個体数のoverflowがないか）を、書き込まずに確認する。
+    fn _ensure_can_mint(to: &T::AccountId, kitty_id: T::Hash) -> Result {
+        // 計算したrandom_hashが衝突していないことを確認する。焼却されたidも衝突とみなす。
+        ensure!(!<KittyOwner<T>>::exists(kitty_id), "Error: the kitty coressponding to this ID already exit!");
+        ensure!(!Self::is_retired(kitty_id), "Error: the kitty coressponding to this ID was burned and can not be reused");
+
+        Self::owned_kitty_count(to).checked_add(1)
+            .ok_or("Error: Overflow happed when trying to register a new kitty in your account balance")?;
+        Self::all_kitties_count().checked_add(1)
+            .ok_or("Error: Overflow happened when trying to register a new kitty")?;
+
+        Ok(())
+    }
+
+    // 売却履歴に(売却額, 現在のブロック)を追加し、PriceHistoryLength件を超えた古い履歴を捨てる。
+    fn _record_sale(kitty_id: T::Hash, price: T::Balance) {
+        let max_len = Self::price_history_length() as usize;
+        if max_len == 0 {
+            return;
+        }
+
+        let now = <system::Module<T>>::block_number();
+        <PriceHistory<T>>::mutate(kitty_id, |history| {
+            history.push((price, now));
+            if history.len() > max_len {
+                let excess = history.len() - max_len;
+                history.drain(..excess);
+            }
+        });
+    }
+
+    // EmitSnapshotsが有効ならば、全kittiesの個体数と`owner`の所有数をStateSnapshotイベントとして吐く。
+    fn _deposit_snapshot(owner: &T::AccountId) {
+        if Self::emit_snapshots() {
+            Self::deposit_event(RawEvent::StateSnapshot(Self::all_kitties_count(), owner.clone(), Self::owned_kitty_count(owner)));
+        }
+    }
+
+    // 新たなkittyを記録するヘルパー関数を用意。
+    // `from_genesis`ならば、世代によらずGenZeroCountとBredCountではなくGenesisCountに数える。
+    fn _mint(to: T::AccountId, kitty_id: T::Hash, mut new_kitty: Kitty<T::Hash, T::Balance, T::BlockNumber>, from_genesis: bool) -> Result {
+        // Verify first, write lastの原則：新しいkittyを記録できることを確認する。
+        Self::_ensure_can_mint(&to, kitty_id)?;
+
+        // Verify first, write lastの原則：この人が現在何匹のkittyを所有しているかを取得する。
+        let owned_kitty_count = Self::owned_kitty_count(&to);
+
+        // Verify first, write lastの原則：新しいkittyを所有するので更新する。
+        let new_owned_kitty_count = owned_kitty_count.checked_add(1)
+            .ok_or("Error: Overflow happed when trying to register a new kitty in your account balance")?;
+
+        // Verify first, write lastの原則：現在登録されているkittiesの個体数を確認する。
+        let all_kitties_count = Self::all_kitties_count();
+
+        // Verify first, write lastの原則：これから登録しようとしているkittyを追加してoverflowしないかを確認する。
+        let new_all_kitties_count = all_kitties_count.checked_add(1)
+            .ok_or("Error: Overflow happened when trying to register a new kitty")?;
+        let creation_count = Self::creation_count();
+        let new_creation_count = creation_count.checked_add(1)
+            .ok_or("Error: Overflow happened when trying to register a new kitty")?;
+
+        // Verify first, write lastの原則：ジェネシスで配ったか、gen 0か、繁殖で生まれたかに応じて、どれかのカウントを増やせることを確認する。
+        let new_kitty_gen = new_kitty.gen;
+        let is_gen_zero = new_kitty_gen == 0;
+        let origin_count = if from_genesis {
+            Self::genesis_count()
+        } else if is_gen_zero {
+            Self::gen_zero_count()
+        } else {
+            Self::bred_count()
+        };
+        let new_origin_count = origin_count.checked_add(1)
+            .ok_or("Error: Overflow happened when counting the new kitty")?;
+
+        // 特徴のビットマスクは必ずdnaから計算し直す。
+        let dna = new_kitty.dna;
+        new_kitty.trait_flags = Self::trait_flags_of(&dna);
+        new_kitty.minted_at = <system::Module<T>>::block_number();
+        new_kitty.last_fed_at = new_kitty.minted_at;
+        new_kitty.last_active_at = new_kitty.minted_at;
+
+        // (random_hash, new_kitty)を登録する。
+        <Kitties<T>>::insert(kitty_id, new_kitty);
+
+        // (生成者を一意に区別するハッシュ値, 生成者)を登録する。
+        <KittyOwner<T>>::insert(kitty_id, &to);
+
+        // 同じdnaのkittyを探せるように記録する。
+        <DnaToKitties<T>>::mutate(dna, |kitty_ids| if kitty_ids.len() < MAX_KITTIES_PER_DNA { kitty_ids.push(kitty_id) });
+
+        // 生み出したアカウントをロイヤリティの受取先として記録する。
+        <KittyMinter<T>>::insert(kitty_id, &to);
+
+        // (all_kitties_count, random_hash)を登録する。all_kitties_countは0オリジンの通し番号となる。
+        <AllKittiesArray<T>>::insert(all_kitties_count, kitty_id);
+
+        // 「現在のkittiesの個体数」を更新する。
+        <AllKittiesCount<T>>::put(new_all_kitties_count);
+
+        // 生まれた順の記録に追記する。
+        <CreationLog<T>>::insert(creation_count, kitty_id);
+        <CreationCount<T>>::put(new_creation_count);
+
+        // 個体数が上限に近づいたら、一度だけ警告する。
+        if !Self::supply_warned() && Self::_supply_threshold_reached(new_all_kitties_count) {
+            <SupplyWarned<T>>::put(true);
+            // ジェネシスで上限を超えて配られていれば、残りは0とする。
+            let remaining = Self::max_total_supply().saturating_sub(new_all_kitties_count);
+            Self::deposit_event(RawEvent::SupplyThresholdReached(remaining));
+        }
+
+        if from_genesis {
+            <GenesisCount<T>>::put(new_origin_count);
+        } else if is_gen_zero {
+            <GenZeroCount<T>>::put(new_origin_count);
+        } else {
+            <BredCount<T>>::put(new_origin_count);
+        }
+        if new_kitty_gen > Self::highest_gen() {
+            <HighestGen<T>>::put(new_kitty_gen);
+        }
+
+        // (random_hash, all_kitties_count)を登録する。
+        <AllKittiesIndex<T>>::insert(kitty_id, all_kitties_count);
+
+        // // (生成者, 生成者を一意に区別するハッシュ値)を登録する。
+        // <OwnedKitty<T>>::insert(&sender, random_hash);
+
+        // ((ユーザー, その人にとって何匹目か), kittyの識別子)を記録する。
+        // こうすることで、二次元リストをエミュレートする。「誰の」「何番目か」で一匹を指定できる。
+        <OwnedKittiesArray<T>>::insert((to.clone(), owned_kitty_count), kitty_id);
+
+        // (ユーザー, ユーザーの所有しているkittyの個体数)を登録する。
+        <OwnedKittiesCount<T>>::insert(&to, new_owned_kitty_count);
+
+        // 今生成されたkittyが、その所有者にとって何番目のkittyなのかを登録する。
+        <OwnedKittiesIndex<T>>::insert(kitty_id, owned_kitty_count);
+
+        // トランザクション執行後のイベントを吐く。
+        Self::deposit_event(RawEvent::Created(to.clone(), kitty_id));
+        Self::_record_event(EventSummary::Minted(to.clone(), kitty_id));
+        Self::_deposit_snapshot(&to);
+
+        Ok(())
+    }
+
+    // 転送元と転送先、転送されるkittyを特定するハッシュ値を引数に、転送を実行しその成否を返すヘルパー関数
+    fn _transfer_from(from: T::AccountId, to: T::AccountId, kitty_id: T::Hash) -> Result {
+
+        // Verify first, write lastの原則：転送できることを確認する。
+        Self::_ensure_can_transfer(&from, &to, kitty_id)?;
+
+        // 所有者の中の何番目のkittyを転送したいのかを確認する。
+        let owned_kitty_count_from = Self::owned_kitty_count(&from);
+
+        // 転送先では何番目のkittyとして扱われるのかを確認する。
+        let owned_kitty_count_to = Self::owned_kitty_count(&to);
+
+        // 転送先がすでにn匹のkittyを所有しているならば、転送先ではn+1匹目として扱われることを確認する。
+        let new_owned_kitty_count_to = owned_kitty_count_to.checked_add(1)
+            .ok_or("Error: happend overflow of `to`'s kitty balance while executing transfer method")?;
+
+        // 転送元がn匹のkittyを所有しているならば、転送してしまうと所有している個体数が1減ることを確認する。
+        let new_owned_kitty_count_from = owned_kitty_count_from.checked_sub(1)
+            .ok_or("Error: happend underflow of `from`'s kitty balance while executing transfer method")?;
+
+        // 転送されるkittyが転送前の所有者にとって何番目の個体なのかを確認する。
+        let kitty_index = <OwnedKittiesIndex<T>>::get(kitty_id);
+
+        // Swap and Popメソッドで転送を実施する。
+        // 転送したいkittyが転送前所有者の最直近に得た個体だったらPopすればいい。
+        // そうでないならば、転送されるkittyの位置に、最直近で得た個体へのポインタを貼る（Swap）。
+        if kitty_index != new_owned_kitty_count_from {
+
+            // 転送元が最直近に所有権を得たkittyのインデックスを確認する。
+            let last_kitty_id = <OwnedKittiesArray<T>>::get((from.clone(), new_owned_kitty_count_from));
+
+            <OwnedKittiesArray<T>>::insert((from.clone(), kitty_index), last_kitty_id);
+            <OwnedKittiesIndex<T>>::insert(last_kitty_id, kitty_index);
+        }
+
+        // 前の所有者に結びついた状態を消す。
+        Self::clear_kitty_state(&kitty_id, &from)?;
+
+        // 転送されたkittyの所有者を更新する。
+        <KittyOwner<T>>::insert(&kitty_id, &to);
+        <Kitties<T>>::mutate(kitty_id, |kitty| Self::_touch(kitty));
+
+        // 転送されたkittyは所有者にとって何番目であるかが変更されたので更新する。
+        <OwnedKittiesIndex<T>>::insert(kitty_id, owned_kitty_count_to);
+
+        // 転送した側は転送して個体数が減ったので更新する。\
+        <OwnedKittiesArray<T>>::remove((from.clone(), new_owned_kitty_count_from));
+
+        // 転送された側は持っている個体数が増えたので更新する。
+        // `to`の`owned_kitty_count_to`匹目が`kitty_id`という意味である。
+        <OwnedKittiesArray<T>>::insert((to.clone(), owned_kitty_count_to), kitty_id);
+
+        //  双方の持っている個体数を更新する。
+        <OwnedKittiesCount<T>>::insert(&from, new_owned_kitty_count_from);
+        <OwnedKittiesCount<T>>::insert(&to, new_owned_kitty_count_to);
+
+        // 所有者が変わったことを他のモジュールに知らせる。
+        T::OnKittyTransferred::on_transferred(&from, &to, &kitty_id);
+
+        // Transferredイベントを吐く。
+        Self::deposit_event(RawEvent::Transferred(from.clone(), to.clone(), kitty_id));
+        Self::_record_event(EventSummary::Transferred(from.clone(), to.clone(), kitty_id));
+        Self::_deposit_snapshot(&from);
+        Self::_deposit_snapshot(&to);
+
+        Ok(())
+    }
+
+    // 焼却されたidに当たってしまったら、別のidが出るまでハッシュし直す。
+    // 試行回数には上限があり、上限に達したidは_ensure_can_mintで衝突として弾かれる。
+    fn _unretired_id(mut kitty_id: T::Hash) -> T::Hash {
+        for _ in 0..MAX_REHASH_ATTEMPTS {
+            if !Self::is_retired(kitty_id) {
+                break;
+            }
+            kitty_id = kitty_id.using_encoded(<T as system::Trait>::Hashing::hash);
+        }
+        kitty_id
+    }
+
+    // kittyを焼却するヘルパー関数。所有権の確認は呼び出し側で行う。
+    fn _burn(owner: T::AccountId, kitty_id: T::Hash) -> Result {
+        // Verify first, write lastの原則：個体数がunderflowしないことを確認する。
+        let new_owned_kitty_count = Self::owned_kitty_count(&owner).checked_sub(1)
+            .ok_or("Error: happend underflow of your kitty balance while executing burn method")?;
+        let new_all_kitties_count = Self::all_kitties_count().checked_sub(1)
+            .ok_or("Error: Underflow happened when trying to burn a kitty")?;
+
+        // 出品中ならば取り下げる。
+        let kitty = Self::kitty(kitty_id);
+        let old_price = kitty.price;
+        Self::_remove_from_for_sale(kitty_id)?;
+        <ListingExpiry<T>>::remove(kitty_id);
+
+        // 所有者のリストからSwap and Popで取り除く。
+        let owned_index = <OwnedKittiesIndex<T>>::take(kitty_id);
+        if owned_index != new_owned_kitty_count {
+            let last_kitty_id = Self::kitty_of_owner_by_index((owner.clone(), new_owned_kitty_count));
+            <OwnedKittiesArray<T>>::insert((owner.clone(), owned_index), last_kitty_id);
+            <OwnedKittiesIndex<T>>::insert(last_kitty_id, owned_index);
+        }
+        <OwnedKittiesArray<T>>::remove((owner.clone(), new_owned_kitty_count));
+        <OwnedKittiesCount<T>>::insert(&owner, new_owned_kitty_count);
+
+        // 全kittiesのリストからSwap and Popで取り除く。
+        let all_index = <AllKittiesIndex<T>>::take(kitty_id);
+        if all_index != new_all_kitties_count {
+            let last_kitty_id = Self::kitty_by_index(new_all_kitties_count);
+            <AllKittiesArray<T>>::insert(all_index, last_kitty_id);
+            <AllKittiesIndex<T>>::insert(last_kitty_id, all_index);
+        }
+        <AllKittiesArray<T>>::remove(new_all_kitties_count);
+        <AllKittiesCount<T>>::put(new_all_kitties_count);
+
+        // 焼却で警告の閾値を下回ったら、次に閾値に達したときにまた警告する。
+        if Self::supply_warned() && !Self::_supply_threshold_reached(new_all_kitties_count) {
+            <SupplyWarned<T>>::kill();
+        }
+
+        // kitty本体と付随する情報を消す。子供のリストは家系をたどれるように残しておく。
+        <Kitties<T>>::remove(kitty_id);
+        Self::_update_floor_price(old_price, Zero::zero());
+        <DnaToKitties<T>>::mutate(kitty.dna, |kitty_ids| kitty_ids.retain(|id| *id != kitty_id));
+        <KittyOwner<T>>::remove(kitty_id);
+        <KittyMinter<T>>::remove(kitty_id);
+        <KittyCollection<T>>::remove(kitty_id);
+        <PriceHistory<T>>::remove(kitty_id);
+        // HasLikedはlikeしたアカウントを列挙できないので消せない。idは二度と使われないので、残っても害はない。
+        <Likes<T>>::remove(kitty_id);
+        <KittyTags<T>>::remove(kitty_id);
+
+        // 同じidが二度と使われないように記録する。
+        <RetiredIds<T>>::insert(kitty_id, true);
+
+        Self::deposit_event(RawEvent::Burned(owner.clone(), kitty_id));
+        Self::_deposit_snapshot(&owner);
+
+        Ok(())
+    }
+
+    // 所有者が変わるときに、前の所有者に結びついた状態を消すヘルパー関数。
+    // 出品は取り下げ、前の所有者のコレクションからは外し、タグと共同所有も消す。新しい所有者が前の所有者の出品価格や持ち物の整理を引き継がないようにする。
+    // 繁殖の申し込みはkittyから引けないので消さないが、approve_breedが承認時に所有者を確かめ直すので使えなくなる。
+    fn clear_kitty_state(kitty_id: &T::Hash, old_owner: &T::AccountId) -> Result {
+        let kitty_id = *kitty_id;
+
+        let mut kitty = Self::kitty(kitty_id);
+        if Self::is_listed(&kitty) {
+            let old_price = kitty.price;
+            Self::_remove_from_for_sale(kitty_id)?;
+            <ListingExpiry<T>>::remove(kitty_id);
+            kitty.price = <T::Balance as As<u64>>::sa(0);
+            <Kitties<T>>::insert(kitty_id, kitty);
+            Self::_update_floor_price(old_price, Zero::zero());
+        }
+
+        if let Some(collection_id) = Self::collection_of(kitty_id) {
+            let in_old_owners_collection = Self::collection(collection_id)
+                .map_or(true, |collection| collection.owner == *old_owner);
+            if in_old_owners_collection {
+                <KittyCollection<T>>::remove(kitty_id);
+                Self::deposit_event(RawEvent::KittyRemovedFromCollection(collection_id, kitty_id));
+            }
+        }
+
+        <KittyTags<T>>::remove(kitty_id);
+
+        // 共同所有は前の所有者との取り決めなので、承認と共に消す。
+        if let Some(co_owner) = <CoOwner<T>>::take(kitty_id) {
+            <CoOwnerApproval<T>>::remove(kitty_id);
+            Self::deposit_event(RawEvent::CoOwnerRemoved(kitty_id, co_owner));
+        }
+
+        Ok(())
+    }
+
+    // kittyのlast_active_atを今のブロックにする。書き戻すのは呼び出し側で行う。
+    fn _touch(kitty: &mut Kitty<T::Hash, T::Balance, T::BlockNumber>) {
+        kitty.last_active_at = <system::Module<T>>::block_number();
+    }
+
+    // 一度の呼び出しで扱う`len`個のkittyや組が、MaxBatchSizeを超えていないことを確認する。
+    fn _ensure_batch_size(len: u64) -> Result {
+        ensure!(len <= u64::from(Self::max_batch_size()), "Error: BatchTooLarge: too many kitties to handle in one call");
+        Ok(())
+    }
+
+    // `who`が`kitty_id`の所有者か共同所有者で、繁殖に使ってよいかどうかを返す。
+    fn _can_breed_with(who: &T::AccountId, kitty_id: T::Hash) -> bool {
+        Self::owner_of(kitty_id).as_ref() == Some(who) || Self::co_owner_of(kitty_id).as_ref() == Some(who)
+    }
+
+    // kittyに共同所有者がいないか、共同所有者が所有者を変えることを承認していればtrueを返す。
+    fn _co_owner_consents(kitty_id: T::Hash) -> bool {
+        !<CoOwner<T>>::exists(kitty_id) || Self::co_owner_approved(kitty_id)
+    }
+
+    // AdoptionCenterに直接送られたkittyは里親募集のキューに入らず、誰にも引き取られなくなる。
+    // AdoptionCenterに預けるのはput_up_for_adoptionだけにする。
+    fn _ensure_not_adoption_center(to: &T::AccountId) -> Result {
+        ensure!(*to != Self::adoption_center(), "Error: use put_up_for_adoption to give a kitty to the adoption center");
+        Ok(())
+    }
+
+    // `from`から`to`に`kitty_id`を転送できるか（所有権、soulbound、個体数のoverflow）を、書き込まずに確認する。
+    fn _ensure_can_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::Hash) -> Result {
+        // 呼び出し元が転送したいkittyの所有者であるかを確認する。
+        let owner = Self::owner_of(kitty_id).ok_or("Error: there is no owner for this kitty")?;
+        ensure!(owner == *from, "Error: `from` account have no ownership for this kitty");
+
+        // 自分自身への転送はSwap and Popで所有リストを壊してしまうので、どの経路でも認めない。
+        ensure!(*from != *to, "Error: you can not transfer a kitty to its current owner");
+
+        // soulboundなkittyはどの経路でも所有者を変えられない。
+        ensure!(!Self::kitty(kitty_id).soulbound, "Error: this kitty is soulbound and can not be transferred");
+
+        // 共同所有者のいるkittyは、売却も含めて共同所有者の承認がなければ所有者を変えられない。
+        ensure!(Self::_co_owner_consents(kitty_id), "Error: CoOwnerApprovalRequired: the co-owner has not approved moving this kitty");
+
+        Self::owned_kitty_count(to).checked_add(1)
+            .ok_or("Error: happend overflow of `to`'s kitty balance while executing transfer method")?;
+        Self::owned_kitty_count(from).checked_sub(1)
+            .ok_or("Error: happend underflow of `from`'s kitty balance while executing transfer method")?;
+
+        Ok(())
+    }
+
+    // イベントの要約をRecentEventsに残す。RecentEventsLengthが0ならば何もしない。
+    fn _record_event(summary: EventSummary<T::AccountId, T::Hash, T::Balance>) {
+        if Self::recent_events_length() == 0 {
+            return;
+        }
+        let now = <system::Module<T>>::block_number();
+        <RecentEvents<T>>::mutate(|events| events.push((now, summary)));
+    }
+
+    // RecentEventsを新しい方からRecentEventsLength件だけ残して、古いものを捨てる。
+    fn _trim_recent_events() {
+        let max_len = Self::recent_events_length() as usize;
+        let mut events = Self::recent_events();
+        if events.len() <= max_len {
+            return;
+        }
+
+        let excess = events.len() - max_len;
+        events.drain(..excess);
+        if events.is_empty() {
+            <RecentEvents<T>>::kill();
+        } else {
+            <RecentEvents<T>>::put(events);
+        }
+    }
+
+    // ブロック`n`で期限切れになる出品を取り下げるヘルパー関数。
+    fn _expire_listings(n: T::BlockNumber) {
+        for kitty_id in <ExpiringAt<T>>::take(n) {
+            // 再出品や売却で期限が変わっている場合、バケツのエントリは古いので無視する。
+            if Self::listing_expiry(kitty_id) != Some(n) {
+                continue;
+            }
+            <ListingExpiry<T>>::remove(kitty_id);
+
+            if !<Kitties<T>>::exists(kitty_id) {
+                continue;
+            }
+
+            // 出品中のリストから外せなければ、出品はそのままにしておく。
+            if Self::_remove_from_for_sale(kitty_id).is_err() {
+                continue;
+            }
+
+            let mut kitty = Self::kitty(kitty_id);
+            let old_price = kitty.price;
+            kitty.price = <T::Balance as As<u64>>::sa(0);
+            <Kitties<T>>::insert(kitty_id, kitty);
+            Self::_update_floor_price(old_price, Zero::zero());
+
+            Self::deposit_event(RawEvent::ListingExpired(kitty_id, n));
+        }
+    }
+}
+
+// テストでストレージを読むための関数。テストでしか使わないので、runtimeには含めない。
+#[cfg(test)]
+impl <T: Trait> Module<T> {
+    // 存在しているkittyの数。
+    pub fn kitty_count() -> u64 {
+        Self::all_kitties_count()
+    }
+
+    // `who`が`kitty_id`を所有していて、所有リストにも載っているかどうか。
+    pub fn owns(who: &T::AccountId, kitty_id: T::Hash) -> bool {
+        Self::owner_of(kitty_id).as_ref() == Some(who)
+            && (0..Self::owned_kitty_count(who)).any(|i| Self::kitty_of_owner_by_index((who.clone(), i)) == kitty_id)
+    }
+
+    // 出品中のリストに載っているkittyのid。リストの順に並ぶ。
+    pub fn for_sale_ids() -> Vec<T::Hash> {
+        (0..Self::for_sale_count()).map(Self::for_sale_by_index).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use runtime_io::with_externalities;
+    use primitives::{H256, Blake2Hasher};
+    use support::{impl_outer_origin, impl_outer_event, assert_ok};
+    use runtime_primitives::{
+        BuildStorage,
+        traits::{BlakeTwo256, IdentityLookup, OnInitialise, OnFinalise},
+        testing::{Digest, DigestItem, Header},
+    };
+
+    impl_outer_origin! {
+        pub enum Origin for Test {}
+    }
+
+    // impl_outer_event!はモジュールを名前で指すので、このモジュールに名前をつける。
+    mod substratekitties {
+        pub use super::super::*;
+    }
+
+    impl_outer_event! {
+        pub enum TestEvent for Test {
+            balances<T>, substratekitties<T>,
+        }
+    }
+
+    // テスト用のruntime。アカウントはu64で、イベントはSystem::events()で読める。
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    pub struct Test;
+
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type Digest = Digest;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<u64>;
+        type Header = Header;
+        type Event = TestEvent;
+        type Log = DigestItem;
+    }
+
+    impl balances::Trait for Test {
+        type Balance = u64;
+        type OnFreeBalanceZero = ();
+        type OnNewAccount = ();
+        type EnsureAccountLiquid = ();
+        type Event = TestEvent;
+    }
+
+    thread_local! {
+        static TRANSFERS: Cell<u32> = Cell::new(0);
+    }
+
+    // OnKittyTransferredが呼ばれた回数を数える。
+    pub struct TransferCounter;
+
+    impl TransferCounter {
+        fn count() -> u32 {
+            TRANSFERS.with(|transfers| transfers.get())
+        }
+    }
+
+    impl OnKittyTransferred<u64, H256> for TransferCounter {
+        fn on_transferred(_from: &u64, _to: &u64, _kitty_id: &H256) {
+            TRANSFERS.with(|transfers| transfers.set(transfers.get() + 1));
+        }
+    }
+
+    impl Trait for Test {
+        type Event = TestEvent;
+        type Randomness = system::Module<Test>;
+        type IsSelfControlled = ();
+        type OnKittyTransferred = TransferCounter;
+        type DnaHashing = BlakeTwo256;
+    }
+
+    type Kitties = Module<Test>;
+    type Balances = balances::Module<Test>;
+    type System = system::Module<Test>;
+
+    const EXISTENTIAL_DEPOSIT: u64 = 50;
+
+    // `balances`の残高を配ったテスト用の状態を作る。出品は5ブロックで期限切れになり、売却額の10%ずつがロイヤリティと焼却に回る。
+    fn new_test_ext(balances: Vec<(u64, u64)>) -> runtime_io::TestExternalities<Blake2Hasher> {
+        new_test_ext_with_kitties(balances, vec![])
+    }
+
+    // ジェネシスで`kitties`を配った状態から始める。
+    fn new_test_ext_with_kitties(balances: Vec<(u64, u64)>, kitties: Vec<(u64, H256, u64)>) -> runtime_io::TestExternalities<Blake2Hasher> {
+        let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+        t.extend(balances::GenesisConfig::<Test> {
+            transaction_base_fee: 0,
+            transaction_byte_fee: 0,
+            existential_deposit: EXISTENTIAL_DEPOSIT,
+            transfer_fee: 0,
+            creation_fee: 0,
+            balances,
+            vesting: vec![],
+        }.build_storage().unwrap().0);
+        t.extend(GenesisConfig::<Test> {
+            listing_duration: 5,
+            royalty_percent: 10,
+            burn_percent: 10,
+            max_batch_size: 10,
+            adoption_center: ADOPTION_CENTER,
+            kitties,
+            ..Default::default()
+        }.build_storage().unwrap().0);
+        t.into()
+    }
+
+    const ADOPTION_CENTER: u64 = 99;
+
+    // 失敗した呼び出しがストレージに何も書き込んでいないことを、状態のルートが変わらないことで確かめる。
+    fn assert_noop<F: FnOnce() -> Result>(call: F, error: &'static str) {
+        let root = runtime_io::storage_root();
+        assert_eq!(call(), Err(error));
+        assert_eq!(runtime_io::storage_root(), root);
+    }
+
+    // `who`にgen 0のkittyを生成し、そのidを返す。
+    fn create(who: u64) -> H256 {
+        let kitty_count = Kitties::kitty_count();
+        assert_ok!(Kitties::create_kitty(Origin::signed(who)));
+        assert_eq!(Kitties::kitty_count(), kitty_count + 1);
+        let kitty_id = Kitties::kitty_by_index(kitty_count);
+        assert!(Kitties::owns(&who, kitty_id));
+        kitty_id
+    }
+
+    // `minter`が生成したkittyを`seller`に渡し、`price`で売りに出す。
+    fn list_minted_by(minter: u64, seller: u64, price: u64) -> H256 {
+        let kitty_id = create(minter);
+        if minter != seller {
+            assert_ok!(Kitties::transfer(Origin::signed(minter), seller, kitty_id.into()));
+        }
+        assert_ok!(Kitties::set_price(Origin::signed(seller), kitty_id.into(), price));
+        kitty_id
+    }
+
+    #[test]
+    fn transfer_to_the_current_owner_does_nothing() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let kitty_id = create(1);
+            assert_ok!(Kitties::set_approval_for_all(Origin::signed(1), 2, true));
+
+            assert_noop(|| Kitties::transfer(Origin::signed(1), 1, kitty_id.into()),
+                "Error: you can not transfer a kitty to its current owner");
+            // 所有者から管理を任されたoperatorが所有者に送り返す場合も同じ。
+            assert_noop(|| Kitties::transfer(Origin::signed(2), 1, kitty_id.into()),
+                "Error: you can not transfer a kitty to its current owner");
+            assert_eq!(Kitties::owned_kitty_count(&1), 1);
+        });
+    }
+
+    #[test]
+    fn listing_expires_at_its_expiry_block() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            System::set_block_number(1);
+            let kitty_id = list_minted_by(1, 1, 100);
+            assert_eq!(Kitties::listing_expiry(kitty_id), Some(6));
+
+            Kitties::on_finalise(5);
+            assert_eq!(Kitties::kitty(kitty_id).price, 100);
+            assert_eq!(Kitties::for_sale_count(), 1);
+
+            Kitties::on_finalise(6);
+            assert_eq!(Kitties::kitty(kitty_id).price, 0);
+            assert_eq!(Kitties::for_sale_count(), 0);
+            assert_eq!(Kitties::listing_expiry(kitty_id), None);
+        });
+    }
+
+    #[test]
+    fn relisting_ignores_the_old_expiry() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            System::set_block_number(1);
+            let kitty_id = list_minted_by(1, 1, 100);
+            assert_ok!(Kitties::set_price(Origin::signed(1), kitty_id.into(), 0));
+
+            System::set_block_number(3);
+            assert_ok!(Kitties::set_price(Origin::signed(1), kitty_id.into(), 150));
+
+            Kitties::on_finalise(6);
+            assert_eq!(Kitties::kitty(kitty_id).price, 150);
+
+            Kitties::on_finalise(8);
+            assert_eq!(Kitties::kitty(kitty_id).price, 0);
+            assert_eq!(Kitties::for_sale_count(), 0);
+        });
+    }
+
+    #[test]
+    fn floor_price_is_rescanned_after_the_floor_listing_leaves() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            System::set_block_number(1);
+            let cheap = list_minted_by(1, 1, 100);
+            list_minted_by(1, 1, 200);
+            assert_eq!(Kitties::floor_price(), Some(100));
+
+            assert_ok!(Kitties::set_price(Origin::signed(1), cheap.into(), 0));
+            assert!(Kitties::floor_rescan().is_some());
+            assert_eq!(Kitties::collection_stats().floor_price, Some(200));
+
+            Kitties::on_finalise(1);
+            assert_eq!(Kitties::floor_rescan(), None);
+            assert_eq!(Kitties::floor_price(), Some(200));
+        });
+    }
+
+    #[test]
+    fn sale_splits_the_price_between_seller_minter_and_burn() {
+        with_externalities(&mut new_test_ext(vec![(1, 1000), (2, 100), (3, 100)]), || {
+            let kitty_id = list_minted_by(3, 2, 200);
+
+            assert_ok!(Kitties::buy_kitty(Origin::signed(1), kitty_id.into(), 200));
+
+            assert!(Kitties::owns(&1, kitty_id));
+            assert_eq!(Kitties::for_sale_count(), 0);
+            assert_eq!(Balances::free_balance(&1), 800);
+            assert_eq!(Balances::free_balance(&2), 260);
+            assert_eq!(Balances::free_balance(&3), 120);
+            assert_eq!(Balances::total_issuance(), 1180);
+        });
+    }
+
+    #[test]
+    fn sale_redirects_an_unpayable_royalty_to_the_seller() {
+        with_externalities(&mut new_test_ext(vec![(1, 1000), (2, 100)]), || {
+            // minterのアカウントは存在せず、ロイヤリティの20はexistential depositに届かない。
+            let kitty_id = list_minted_by(3, 2, 200);
+
+            assert_ok!(Kitties::buy_kitty(Origin::signed(1), kitty_id.into(), 200));
+
+            assert!(Kitties::owns(&1, kitty_id));
+            assert_eq!(Balances::free_balance(&1), 800);
+            assert_eq!(Balances::free_balance(&2), 280);
+            assert_eq!(Balances::free_balance(&3), 0);
+        });
+    }
+
+    #[test]
+    fn sale_that_would_reap_a_kept_alive_buyer_does_nothing() {
+        with_externalities(&mut new_test_ext(vec![(1, 200), (2, 100), (3, 100)]), || {
+            let kitty_id = list_minted_by(3, 2, 200);
+
+            assert_noop(|| Kitties::buy_kitty(Origin::signed(1), kitty_id.into(), 200),
+                "Error: buying this kitty would leave your balance below the existential deposit");
+            assert!(Kitties::owns(&2, kitty_id));
+        });
+    }
+
+    #[test]
+    fn sale_may_reap_the_buyer_only_with_the_last_payment() {
+        with_externalities(&mut new_test_ext(vec![(1, 200), (2, 100), (3, 100)]), || {
+            <BuyerKeepAlive<Test>>::put(false);
+            let kitty_id = list_minted_by(3, 2, 200);
+
+            assert_ok!(Kitties::buy_kitty(Origin::signed(1), kitty_id.into(), 200));
+
+            assert!(Kitties::owns(&1, kitty_id));
+            assert_eq!(Balances::free_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&2), 260);
+            assert_eq!(Balances::free_balance(&3), 120);
+        });
+    }
+
+    #[test]
+    fn sale_that_would_reap_the_buyer_before_the_last_payment_does_nothing() {
+        with_externalities(&mut new_test_ext(vec![(1, 200), (2, 100), (3, 100)]), || {
+            <BuyerKeepAlive<Test>>::put(false);
+            // 売却側に渡るのは10だけなので、ロイヤリティと焼却の後に残高が50を下回ってしまう。
+            <BurnPercent<Test>>::put(85);
+            let kitty_id = list_minted_by(3, 2, 200);
+
+            assert_noop(|| Kitties::buy_kitty(Origin::signed(1), kitty_id.into(), 200),
+                "Error: buying this kitty would empty your account before the sale is settled");
+        });
+    }
+
+    #[test]
+    fn soulbound_kitty_can_not_be_listed() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            assert_ok!(Kitties::create_soulbound_kitty(Origin::from(system::RawOrigin::<u64>::Root), 1));
+            let kitty_id = Kitties::kitty_of_owner_by_index((1, 0));
+
+            assert_noop(|| Kitties::set_price(Origin::signed(1), kitty_id.into(), 100),
+                "Error: this kitty is soulbound and can not be listed for sale");
+        });
+    }
+
+    #[test]
+    fn burn_compacts_the_indices_and_keeps_the_creation_order() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let first = create(1);
+            let second = create(1);
+            let third = create(1);
+
+            assert_ok!(Kitties::burn_kitty(Origin::signed(1), first.into()));
+
+            // 最後のkittyが焼却されたkittyの位置に移る。
+            assert_eq!(Kitties::all_kitties_count(), 2);
+            assert_eq!(Kitties::kitty_by_index(0), third);
+            assert_eq!(Kitties::kitty_by_index(1), second);
+            assert_eq!(<AllKittiesIndex<Test>>::get(third), 0);
+
+            assert_eq!(Kitties::owned_kitty_count(&1), 2);
+            assert_eq!(Kitties::kitty_of_owner_by_index((1, 0)), third);
+            assert_eq!(Kitties::kitty_of_owner_by_index((1, 1)), second);
+            assert_eq!(<OwnedKittiesIndex<Test>>::get(third), 0);
+
+            // 生まれた順の記録は詰められず、焼却されたkittyは飛ばされる。
+            assert_eq!(Kitties::creation_count(), 3);
+            let recent: Vec<H256> = Kitties::recent_kitties(10).into_iter().map(|(kitty_id, _)| kitty_id).collect();
+            assert_eq!(recent, vec![third, second]);
+        });
+    }
+
+    #[test]
+    fn breeding_requires_owning_both_parents() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let mine = create(1);
+            let theirs = create(2);
+
+            assert_noop(|| Kitties::breed_kitty(Origin::signed(1), mine.into(), theirs.into()),
+                "Error: you have no ownership to this cat 2");
+            assert_noop(|| Kitties::breed_and_gift(Origin::signed(1), theirs.into(), mine.into(), 3),
+                "Error: you have no ownership to this cat 1");
+            assert_noop(|| Kitties::breed_many(Origin::signed(1), vec![(mine.into(), theirs.into())]),
+                "Error: you have no ownership to this cat 2");
+            assert_eq!(Kitties::validate_sequence(1, vec![Action::Breed(mine.into(), theirs.into())]),
+                Err((0, b"Error: you have no ownership to this cat 2".to_vec())));
+
+            let other = create(1);
+            assert_ok!(Kitties::breed_kitty(Origin::signed(1), mine.into(), other.into()));
+            assert_eq!(Kitties::owned_kitty_count(&1), 3);
+        });
+    }
+
+    #[test]
+    fn breed_many_with_one_invalid_pair_breeds_nothing() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let (a, b, c) = (create(1), create(1), create(1));
+            let theirs = create(2);
+
+            assert_noop(|| Kitties::breed_many(Origin::signed(1), vec![(a.into(), b.into()), (c.into(), theirs.into())]),
+                "Error: you have no ownership to this cat 2");
+            assert_eq!(Kitties::owned_kitty_count(&1), 3);
+        });
+    }
+
+    #[test]
+    fn breed_many_that_would_reap_the_breeder_midway_breeds_nothing() {
+        with_externalities(&mut new_test_ext(vec![(1, 10_000)]), || {
+            <BreedingFee<Test>>::put(30);
+            let (a, b, c, d) = (create(1), create(1), create(1), create(1));
+            let fee = |x: H256, y: H256| 30 * u64::from(1 + Kitties::rarity_tier(&Kitties::kitty(x)) + Kitties::rarity_tier(&Kitties::kitty(y)));
+            let total_fee = fee(a, b) + fee(c, d);
+            // 手数料の合計は払えるが、払った後の残高はexistential depositを下回る。
+            Balances::slash(&1, 10_000 - total_fee - 10);
+
+            assert_noop(|| Kitties::breed_many(Origin::signed(1), vec![(a.into(), b.into()), (c.into(), d.into())]),
+                "Error: InsufficientBalance: paying the breeding fees would leave your balance below the existential deposit");
+            assert_eq!(Kitties::owned_kitty_count(&1), 4);
+        });
+    }
+
+    #[test]
+    fn co_owner_can_breed_the_kitty() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let mine = create(1);
+            let shared = create(2);
+            assert_ok!(Kitties::add_co_owner(Origin::signed(2), shared.into(), 1));
+
+            assert_ok!(Kitties::breed_kitty(Origin::signed(1), mine.into(), shared.into()));
+            assert_eq!(Kitties::owned_kitty_count(&1), 2);
+            assert_eq!(Kitties::owned_kitty_count(&2), 1);
+        });
+    }
+
+    #[test]
+    fn approved_breed_pays_the_reserved_offer_fee() {
+        with_externalities(&mut new_test_ext(vec![(1, 1000), (2, 100)]), || {
+            let mine = create(1);
+            let theirs = create(2);
+            assert_ok!(Kitties::request_breed(Origin::signed(1), mine.into(), theirs.into(), 30));
+            assert_eq!(Balances::reserved_balance(&1), 30);
+
+            assert_ok!(Kitties::approve_breed(Origin::signed(2), theirs.into(), mine.into()));
+
+            assert_eq!(Kitties::owned_kitty_count(&1), 2);
+            assert_eq!(Kitties::owned_kitty_count(&2), 1);
+            assert_eq!(Kitties::breed_request((mine, theirs)), None);
+            assert_eq!(Balances::reserved_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&1), 970);
+            assert_eq!(Balances::free_balance(&2), 130);
+        });
+    }
+
+    #[test]
+    fn children_of_the_same_pair_are_siblings() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let mother = create(1);
+            let father = create(1);
+            let other = create(1);
+            assert_ok!(Kitties::breed_kitty(Origin::signed(1), mother.into(), father.into()));
+            let first = Kitties::kitty_of_owner_by_index((1, 3));
+            assert_ok!(Kitties::breed_kitty(Origin::signed(1), mother.into(), father.into()));
+            let second = Kitties::kitty_of_owner_by_index((1, 4));
+            assert_ok!(Kitties::breed_kitty(Origin::signed(1), mother.into(), other.into()));
+            let half = Kitties::kitty_of_owner_by_index((1, 5));
+
+            assert_eq!(Kitties::children_count(&mother), 3);
+            assert_eq!(Kitties::children_count(&father), 2);
+            // 両親とも同じ兄弟姉妹は一度だけ返す。
+            assert_eq!(Kitties::siblings_of(first, 0, 100), vec![second, half]);
+            assert_eq!(Kitties::siblings_of(second, 0, 100), vec![first, half]);
+            assert_eq!(Kitties::siblings_of(half, 0, 100), vec![first, second]);
+            assert_eq!(Kitties::siblings_of(first, 1, 1), vec![second]);
+            assert!(Kitties::siblings_of(mother, 0, 100).is_empty());
+        });
+    }
+
+    #[test]
+    fn abandoned_kitty_can_be_claimed_only_after_the_period() {
+        with_externalities(&mut new_test_ext(vec![(1, 100), (2, 100)]), || {
+            <AbandonmentPeriod<Test>>::put(10);
+            System::set_block_number(1);
+            let kitty_id = create(2);
+            System::inc_account_nonce(&2);
+
+            assert_noop(|| Kitties::claim_abandoned(Origin::signed(1), kitty_id.into()),
+                "Error: the owner of this kitty still exists");
+
+            // 所有者の残高を全て失わせてreapする。
+            Balances::slash(&2, 100);
+            assert!(Balances::total_balance(&2).is_zero());
+
+            System::set_block_number(10);
+            assert_noop(|| Kitties::claim_abandoned(Origin::signed(1), kitty_id.into()),
+                "Error: this kitty has not been abandoned long enough");
+
+            System::set_block_number(11);
+            assert_ok!(Kitties::claim_abandoned(Origin::signed(1), kitty_id.into()));
+            assert!(Kitties::owns(&1, kitty_id));
+        });
+    }
+
+    #[test]
+    fn kitty_of_a_never_active_owner_can_not_be_claimed() {
+        with_externalities(&mut new_test_ext(vec![(1, 100)]), || {
+            <AbandonmentPeriod<Test>>::put(10);
+            System::set_block_number(1);
+            let kitty_id = create(3);
+
+            System::set_block_number(11);
+            assert_noop(|| Kitties::claim_abandoned(Origin::signed(1), kitty_id.into()),
+                "Error: the owner of this kitty has never been active");
+        });
+    }
+
+    #[test]
+    fn breed_many_accepts_max_batch_size_pairs_and_rejects_more() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let pairs: Vec<(KittyId<H256>, KittyId<H256>)> = (0..11).map(|_| (create(1).into(), create(1).into())).collect();
+
+            assert_noop(|| Kitties::breed_many(Origin::signed(1), pairs.clone()),
+                "Error: BatchTooLarge: too many kitties to handle in one call");
+
+            assert_ok!(Kitties::breed_many(Origin::signed(1), pairs[..10].to_vec()));
+            assert_eq!(Kitties::owned_kitty_count(&1), 32);
+        });
+    }
+
+    #[test]
+    fn transfer_all_accepts_max_batch_size_and_rejects_more() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            for _ in 0..11 {
+                create(1);
+            }
+
+            assert_noop(|| Kitties::transfer_all(Origin::signed(1), 2, 11),
+                "Error: BatchTooLarge: too many kitties to handle in one call");
+
+            assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, 10));
+            assert_eq!(Kitties::owned_kitty_count(&1), 1);
+            assert_eq!(Kitties::owned_kitty_count(&2), 10);
+        });
+    }
+
+    #[test]
+    fn cancel_all_listings_accepts_max_batch_size_and_rejects_more() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            for _ in 0..11 {
+                list_minted_by(1, 1, 100);
+            }
+
+            assert_noop(|| Kitties::cancel_all_listings(Origin::signed(1), 0, 11),
+                "Error: BatchTooLarge: too many kitties to handle in one call");
+
+            assert_ok!(Kitties::cancel_all_listings(Origin::signed(1), 0, 10));
+            assert_eq!(Kitties::for_sale_ids(), vec![Kitties::kitty_of_owner_by_index((1, 10))]);
+        });
+    }
+
+    #[test]
+    fn kitties_can_not_be_sent_to_the_adoption_center_directly() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let kitty_id = create(1);
+            let error = "Error: use put_up_for_adoption to give a kitty to the adoption center";
+
+            assert_noop(|| Kitties::transfer(Origin::signed(1), ADOPTION_CENTER, kitty_id.into()), error);
+            assert_noop(|| Kitties::transfer_with_memo(Origin::signed(1), ADOPTION_CENTER, kitty_id.into(), b"bye".to_vec()), error);
+            assert_noop(|| Kitties::transfer_all(Origin::signed(1), ADOPTION_CENTER, 1), error);
+        });
+    }
+
+    #[test]
+    fn adopted_kitties_come_out_in_the_order_they_were_put_up() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let (a, b, c) = (create(1), create(1), create(1));
+            assert_ok!(Kitties::put_up_for_adoption(Origin::signed(1), a.into()));
+            assert_ok!(Kitties::put_up_for_adoption(Origin::signed(1), b.into()));
+            assert_ok!(Kitties::put_up_for_adoption(Origin::signed(1), c.into()));
+            assert!(Kitties::owns(&ADOPTION_CENTER, a));
+            assert_eq!(Kitties::adoption_pool_count(), 3);
+
+            assert_ok!(Kitties::adopt(Origin::signed(2)));
+            assert!(Kitties::owns(&2, a));
+            assert_ok!(Kitties::adopt(Origin::signed(3)));
+            assert!(Kitties::owns(&3, b));
+            assert_ok!(Kitties::adopt(Origin::signed(2)));
+            assert!(Kitties::owns(&2, c));
+
+            assert_eq!(Kitties::adoption_pool_count(), 0);
+            assert_noop(|| Kitties::adopt(Origin::signed(3)), "Error: NothingToAdopt: there are no kitties up for adoption");
+        });
+    }
+
+    #[test]
+    fn genesis_kitties_are_counted_apart_from_gen_zero_and_bred_kitties() {
+        let gen_zero = H256::from([1u8; 32]);
+        let gen_three = H256::from([2u8; 32]);
+        with_externalities(&mut new_test_ext_with_kitties(vec![], vec![(1, gen_zero, 0), (2, gen_three, 3)]), || {
+            assert_eq!(Kitties::kitty_count(), 2);
+            assert!(Kitties::owns(&1, gen_zero));
+            assert!(Kitties::owns(&2, gen_three));
+            assert_eq!(Kitties::kitty(gen_three).gen, 3);
+
+            assert_eq!(Kitties::genesis_count(), 2);
+            assert_eq!(Kitties::gen_zero_count(), 0);
+            assert_eq!(Kitties::bred_count(), 0);
+
+            create(1);
+            assert_eq!(Kitties::genesis_count(), 2);
+            assert_eq!(Kitties::gen_zero_count(), 1);
+        });
+    }
+
+    #[test]
+    fn likes_are_counted_once_per_account() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let kitty_id = create(1);
+
+            assert_ok!(Kitties::like_kitty(Origin::signed(2), kitty_id.into()));
+            assert_ok!(Kitties::like_kitty(Origin::signed(2), kitty_id.into()));
+            assert_eq!(Kitties::likes_of(kitty_id), 1);
+            assert_ok!(Kitties::like_kitty(Origin::signed(3), kitty_id.into()));
+            assert_eq!(Kitties::likes_of(kitty_id), 2);
+
+            assert_ok!(Kitties::unlike_kitty(Origin::signed(2), kitty_id.into()));
+            assert_eq!(Kitties::likes_of(kitty_id), 1);
+            assert!(!Kitties::has_liked((kitty_id, 2)));
+            // likeしていなければ何もしない。
+            let root = runtime_io::storage_root();
+            assert_ok!(Kitties::unlike_kitty(Origin::signed(2), kitty_id.into()));
+            assert_eq!(runtime_io::storage_root(), root);
+        });
+    }
+
+    #[test]
+    fn liked_kitty_can_be_unliked_after_it_is_burned() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let kitty_id = create(1);
+            assert_ok!(Kitties::like_kitty(Origin::signed(2), kitty_id.into()));
+            assert_ok!(Kitties::burn_kitty(Origin::signed(1), kitty_id.into()));
+            assert_eq!(Kitties::likes_of(kitty_id), 0);
+
+            assert_ok!(Kitties::unlike_kitty(Origin::signed(2), kitty_id.into()));
+            assert!(!Kitties::has_liked((kitty_id, 2)));
+            assert_noop(|| Kitties::like_kitty(Origin::signed(2), kitty_id.into()), "Error: invalid kitty id: this kitty does not exist");
+        });
+    }
+
+    #[test]
+    fn crafted_kitty_has_every_trait_of_the_recipe() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let input = create(1);
+            let input_tier = Kitties::rarity_tier(&Kitties::kitty(input));
+            assert_ok!(Kitties::set_recipe(Origin::from(system::RawOrigin::<u64>::Root), 7, 1, input_tier, 0xFFFF_FFFF));
+
+            assert_ok!(Kitties::craft(Origin::signed(1), 7, vec![input.into()]));
+            assert!(Kitties::is_retired(input));
+            let crafted = Kitties::kitty_of_owner_by_index((1, 0));
+            assert_eq!(Kitties::kitty(crafted).trait_flags, 0xFFFF_FFFF);
+        });
+    }
+
+    #[test]
+    fn recipe_with_traits_beyond_the_dna_is_rejected() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let root = || Origin::from(system::RawOrigin::<u64>::Root);
+
+            assert_noop(|| Kitties::set_recipe(root(), 7, 1, 0, 1 << 32),
+                "Error: a recipe can not give traits beyond the length of the dna");
+            assert_noop(|| Kitties::set_recipe(root(), 7, 0, 0, 1),
+                "Error: a recipe must burn at least one kitty");
+            assert_noop(|| Kitties::set_recipe(Origin::signed(1), 7, 1, 0, 1),
+                "Error: only root or the admin can call this function");
+        });
+    }
+
+    #[test]
+    fn co_owned_kitty_moves_only_after_the_co_owner_approves() {
+        with_externalities(&mut new_test_ext(vec![(1, 100), (3, 1000)]), || {
+            let transferred = create(1);
+            let sold = list_minted_by(1, 1, 100);
+            assert_ok!(Kitties::add_co_owner(Origin::signed(1), transferred.into(), 2));
+            assert_ok!(Kitties::add_co_owner(Origin::signed(1), sold.into(), 2));
+
+            assert_noop(|| Kitties::transfer(Origin::signed(1), 3, transferred.into()),
+                "Error: CoOwnerApprovalRequired: the co-owner has not approved moving this kitty");
+            assert_noop(|| Kitties::buy_kitty(Origin::signed(3), sold.into(), 100),
+                "Error: CoOwnerApprovalRequired: the co-owner has not approved moving this kitty");
+
+            assert_ok!(Kitties::set_co_owner_approval(Origin::signed(2), transferred.into(), true));
+            assert_ok!(Kitties::set_co_owner_approval(Origin::signed(2), sold.into(), true));
+
+            assert_ok!(Kitties::transfer(Origin::signed(1), 3, transferred.into()));
+            assert_ok!(Kitties::buy_kitty(Origin::signed(3), sold.into(), 100));
+            assert!(Kitties::owns(&3, transferred));
+            assert!(Kitties::owns(&3, sold));
+            // 共同所有は前の所有者との取り決めなので、所有者が変われば消える。
+            assert_eq!(Kitties::co_owner_of(transferred), None);
+            assert_eq!(Kitties::co_owner_of(sold), None);
+        });
+    }
+
+    #[test]
+    fn on_kitty_transferred_fires_once_per_transfer_and_sale() {
+        with_externalities(&mut new_test_ext(vec![(1, 100), (3, 1000)]), || {
+            let transferred = create(1);
+            let sold = list_minted_by(1, 1, 100);
+            let before = TransferCounter::count();
+
+            assert_noop(|| Kitties::transfer(Origin::signed(2), 3, transferred.into()),
+                "Error: you have no ownership for this kitty");
+            assert_eq!(TransferCounter::count(), before);
+
+            assert_ok!(Kitties::transfer(Origin::signed(1), 2, transferred.into()));
+            assert_eq!(TransferCounter::count(), before + 1);
+
+            assert_ok!(Kitties::buy_kitty(Origin::signed(3), sold.into(), 100));
+            assert_eq!(TransferCounter::count(), before + 2);
+        });
+    }
+
+    #[test]
+    fn transfer_all_moves_the_tail_and_rebuilds_both_owned_lists() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            let (a, b, c, d) = (create(1), create(1), create(1), create(1));
+            let e = create(2);
+
+            assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, 3));
+
+            // 所有リストの通し番号と、kittyごとの通し番号の記録が一致していなければならない。
+            let assert_owned_list = |who: u64, expected: &[H256]| {
+                assert_eq!(Kitties::owned_kitty_count(&who), expected.len() as u64);
+                for (i, kitty_id) in expected.iter().enumerate() {
+                    assert_eq!(Kitties::kitty_of_owner_by_index((who, i as u64)), *kitty_id);
+                    assert_eq!(<OwnedKittiesIndex<Test>>::get(kitty_id), i as u64);
+                    assert!(Kitties::owns(&who, *kitty_id));
+                }
+            };
+            assert_owned_list(1, &[a]);
+            assert_owned_list(2, &[e, d, c, b]);
+        });
+    }
+
+    #[test]
+    fn starter_pack_can_be_claimed_only_once() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            <StarterPackSize<Test>>::put(3);
+
+            assert_ok!(Kitties::claim_starter_pack(Origin::signed(1)));
+            assert_eq!(Kitties::owned_kitty_count(&1), 3);
+            assert_eq!(Kitties::kitty_count(), 3);
+            assert!(Kitties::claimed_starter(&1));
+
+            assert_noop(|| Kitties::claim_starter_pack(Origin::signed(1)),
+                "Error: StarterAlreadyClaimed: you have already claimed your starter pack");
+            assert_eq!(Kitties::owned_kitty_count(&1), 3);
+        });
+    }
+
+    #[test]
+    fn supply_warning_is_deposited_exactly_once() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            <MaxTotalSupply<Test>>::put(4);
+            <SupplyWarningPercent<Test>>::put(50);
+
+            create(1);
+            assert!(!Kitties::supply_warned());
+            for _ in 0..3 {
+                create(1);
+            }
+
+            // 2匹目で上限の50%に届き、残りは2匹だった。その後は何匹生まれても警告しない。
+            let warnings: Vec<u64> = System::events().into_iter()
+                .filter_map(|record| match record.event {
+                    TestEvent::substratekitties(RawEvent::SupplyThresholdReached(remaining)) => Some(remaining),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(warnings, vec![2]);
+            assert!(Kitties::supply_warned());
+        });
+    }
+
+    #[test]
+    fn recent_events_are_trimmed_to_the_last_ones() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            <RecentEventsLength<Test>>::put(2);
+            System::set_block_number(1);
+            let (_, b, c) = (create(1), create(1), create(1));
+            assert_eq!(Kitties::recent_events().len(), 3);
+
+            // 次のブロックの最初に、新しい方から2件だけ残す。
+            System::set_block_number(2);
+            Kitties::on_initialise(2);
+            assert_eq!(Kitties::recent_events(), vec![
+                (1, EventSummary::Minted(1, b)),
+                (1, EventSummary::Minted(1, c)),
+            ]);
+
+            <RecentEventsLength<Test>>::put(0);
+            Kitties::on_initialise(3);
+            assert!(Kitties::recent_events().is_empty());
+        });
+    }
+
+    #[test]
+    fn portfolio_value_sums_only_the_listed_kitties_of_the_page() {
+        with_externalities(&mut new_test_ext(vec![]), || {
+            list_minted_by(1, 1, 100);
+            create(1);
+            list_minted_by(1, 1, 30);
+            // 他のアカウントの出品は数えない。
+            list_minted_by(2, 2, 500);
+
+            assert_eq!(Kitties::portfolio_value(1, 0, 100), 130);
+            assert_eq!(Kitties::portfolio_value(1, 0, 2), 100);
+            assert_eq!(Kitties::portfolio_value(1, 1, 2), 30);
+            assert_eq!(Kitties::portfolio_value(1, 3, 100), 0);
+            assert_eq!(Kitties::portfolio_value(3, 0, 100), 0);
+        });
+    }
+}
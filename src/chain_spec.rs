@@ -1,7 +1,7 @@
 use primitives::{Ed25519AuthorityId, ed25519};
 use node_template_runtime::{
 	AccountId, GenesisConfig, ConsensusConfig, TimestampConfig, BalancesConfig,
-	SudoConfig, IndicesConfig
+	SudoConfig, IndicesConfig, SubstratekittiesConfig
 };
 use substrate_service;
 
@@ -101,5 +101,41 @@ fn testnet_genesis(initial_authorities: Vec<Ed25519AuthorityId>, endowed_account
 		sudo: Some(SudoConfig {
 			key: root_key,
 		}),
+		substratekitties: Some(SubstratekittiesConfig {
+			listing_duration: 17280,	// Listings expire after a day of 5 second blocks.
+			breeding_cooldown: 60,		// A gen-0 kitty can breed again after 5 minutes.
+			gen_zero_exempt_from_cooldown: false,
+			royalty_percent: 5,
+			burn_percent: 1,
+			buyer_keep_alive: true,
+			starter_pack_size: 3,
+			max_breeds_per_period: 10,
+			breed_period: 17280,		// One day of 5 second blocks.
+			mint_protection: 0,
+			breeding_fee: 1000,
+			breed_failure_chance_bps: 0,
+			forbid_sibling_breeding: true,
+			min_breeding_age: 10,
+			transfer_fee: 100,
+			fee_collector: root_key,
+			// Nobody holds a key for this account, so pooled kitties only leave it through `adopt`.
+			adoption_center: AccountId::from(*b"substratekitties/adoption-center"),
+			emit_snapshots: false,
+			price_history_length: 10,
+			abandonment_period: 518400,	// 30 days of 5 second blocks.
+			happiness_decay_period: 1728,	// A fed kitty is unhappy after ten days of 5 second blocks.
+			feeding_fee: 10,
+			min_breeding_happiness: 50,
+			max_mutation_delta: 16,
+			max_bred_per_gen_zero: 10,
+			litter_size: 1,
+			max_mints_per_block: 50,
+			max_batch_size: 10,
+			max_total_supply: 0,
+			supply_warning_percent: 90,
+			recent_events_length: 100,
+			spotlight_period: 17280,	// One day of 5 second blocks.
+			kitties: vec![],
+		}),
 	}
 }